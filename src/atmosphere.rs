@@ -0,0 +1,163 @@
+use nalgebra_glm::{Vec3, dot};
+use std::f32::consts::PI;
+
+// Capa de atmósfera alrededor de un cuerpo. La luz del Sol se dispersa por moléculas
+// (Rayleigh, más fuerte en el azul) y por partículas grandes (Mie, casi sin color y hacia
+// delante). La densidad de ambas cae de forma exponencial con la altura.
+// Todas las distancias están en unidades de mundo.
+#[derive(Debug, Clone, Copy)]
+pub struct Atmosphere {
+    pub planet_radius: f32,
+    pub atmosphere_radius: f32,
+    pub rayleigh_scale_height: f32,
+    pub mie_scale_height: f32,
+    // Coeficientes de dispersión a nivel de la superficie (por unidad de distancia)
+    pub rayleigh_coefficients: Vec3,
+    pub mie_coefficient: f32,
+    // Anisotropía de Mie (g): cuanto más cerca de 1, más se concentra la luz alrededor del Sol
+    pub mie_anisotropy: f32,
+    pub sun_intensity: f32,
+    pub view_samples: u32,
+    pub light_samples: u32,
+}
+
+impl Atmosphere {
+    // Valores de la Tierra escalados a un planeta de radio 1 con una atmósfera exagerada
+    pub fn earth(planet_radius: f32) -> Self {
+        Atmosphere {
+            planet_radius,
+            atmosphere_radius: planet_radius * 1.12,
+            rayleigh_scale_height: planet_radius * 0.03,
+            mie_scale_height: planet_radius * 0.012,
+            rayleigh_coefficients: Vec3::new(1.55, 3.6, 8.8) / planet_radius,
+            mie_coefficient: 2.1 / planet_radius,
+            mie_anisotropy: 0.76,
+            sun_intensity: 8.0,
+            view_samples: 16,
+            light_samples: 8,
+        }
+    }
+
+    // Urano y Neptuno: el metano absorbe el rojo y deja un tono cian
+    pub fn ice_giant(planet_radius: f32) -> Self {
+        Atmosphere {
+            rayleigh_coefficients: Vec3::new(0.6, 3.8, 5.2) / planet_radius,
+            mie_coefficient: 1.0 / planet_radius,
+            ..Atmosphere::earth(planet_radius)
+        }
+    }
+
+    // Júpiter y Saturno: neblina de partículas más densa y cálida
+    pub fn gas_giant(planet_radius: f32) -> Self {
+        Atmosphere {
+            atmosphere_radius: planet_radius * 1.08,
+            rayleigh_scale_height: planet_radius * 0.02,
+            mie_scale_height: planet_radius * 0.015,
+            rayleigh_coefficients: Vec3::new(1.8, 1.5, 1.0) / planet_radius,
+            mie_coefficient: 4.0 / planet_radius,
+            mie_anisotropy: 0.7,
+            ..Atmosphere::earth(planet_radius)
+        }
+    }
+
+    // Venus: sobre las nubes queda una neblina de gotas de ácido sulfúrico que enrojece
+    // la luz rasante y se ve como un borde amarillento grueso
+    pub fn venus(planet_radius: f32) -> Self {
+        Atmosphere {
+            atmosphere_radius: planet_radius * 1.1,
+            rayleigh_scale_height: planet_radius * 0.025,
+            mie_scale_height: planet_radius * 0.03,
+            rayleigh_coefficients: Vec3::new(1.6, 1.3, 0.7) / planet_radius,
+            mie_coefficient: 5.0 / planet_radius,
+            mie_anisotropy: 0.75,
+            ..Atmosphere::earth(planet_radius)
+        }
+    }
+
+    // Luz dispersada hacia `origin` a lo largo del rayo `direction` (normalizado) para una
+    // atmósfera centrada en `center`, iluminada desde `sun_direction`
+    pub fn scatter(&self, center: &Vec3, origin: &Vec3, direction: &Vec3, sun_direction: &Vec3) -> Vec3 {
+        let Some((enter, exit)) = ray_sphere(origin, direction, center, self.atmosphere_radius) else {
+            return Vec3::zeros();
+        };
+        let start = enter.max(0.0);
+        // El rayo termina al chocar con la superficie del planeta
+        let end = match ray_sphere(origin, direction, center, self.planet_radius) {
+            Some((hit, _)) if hit > 0.0 => exit.min(hit),
+            _ => exit,
+        };
+        if end <= start {
+            return Vec3::zeros();
+        }
+
+        let step = (end - start) / self.view_samples as f32;
+        let mut optical_depth_rayleigh = 0.0;
+        let mut optical_depth_mie = 0.0;
+        let mut total_rayleigh = Vec3::zeros();
+        let mut total_mie = Vec3::zeros();
+
+        for i in 0..self.view_samples {
+            let sample = origin + direction * (start + (i as f32 + 0.5) * step);
+            let height = (sample - center).magnitude() - self.planet_radius;
+            let density_rayleigh = (-height / self.rayleigh_scale_height).exp() * step;
+            let density_mie = (-height / self.mie_scale_height).exp() * step;
+            optical_depth_rayleigh += density_rayleigh;
+            optical_depth_mie += density_mie;
+
+            let Some((light_rayleigh, light_mie)) = self.light_optical_depth(center, &sample, sun_direction) else {
+                continue;
+            };
+
+            let tau = self.rayleigh_coefficients * (optical_depth_rayleigh + light_rayleigh)
+                + Vec3::repeat(self.mie_coefficient * 1.1 * (optical_depth_mie + light_mie));
+            let attenuation = tau.map(|t| (-t).exp());
+            total_rayleigh += attenuation * density_rayleigh;
+            total_mie += attenuation * density_mie;
+        }
+
+        let mu = dot(direction, sun_direction);
+        let phase_rayleigh = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+        let g = self.mie_anisotropy;
+        let phase_mie = 3.0 / (8.0 * PI) * ((1.0 - g * g) * (1.0 + mu * mu))
+            / ((2.0 + g * g) * (1.0 + g * g - 2.0 * g * mu).powf(1.5));
+
+        (total_rayleigh.component_mul(&self.rayleigh_coefficients) * phase_rayleigh
+            + total_mie * self.mie_coefficient * phase_mie)
+            * self.sun_intensity
+    }
+
+    // Profundidad óptica desde `point` hasta el borde de la atmósfera en dirección al Sol;
+    // None si el planeta tapa al Sol
+    fn light_optical_depth(&self, center: &Vec3, point: &Vec3, sun_direction: &Vec3) -> Option<(f32, f32)> {
+        if let Some((hit, _)) = ray_sphere(point, sun_direction, center, self.planet_radius) {
+            if hit > 0.0 {
+                return None;
+            }
+        }
+        let (_, exit) = ray_sphere(point, sun_direction, center, self.atmosphere_radius)?;
+        let step = exit / self.light_samples as f32;
+
+        let mut rayleigh = 0.0;
+        let mut mie = 0.0;
+        for i in 0..self.light_samples {
+            let sample = point + sun_direction * ((i as f32 + 0.5) * step);
+            let height = (sample - center).magnitude() - self.planet_radius;
+            rayleigh += (-height / self.rayleigh_scale_height).exp() * step;
+            mie += (-height / self.mie_scale_height).exp() * step;
+        }
+        Some((rayleigh, mie))
+    }
+}
+
+// Distancias de entrada y salida del rayo en la esfera (pueden ser negativas si está detrás)
+fn ray_sphere(origin: &Vec3, direction: &Vec3, center: &Vec3, radius: f32) -> Option<(f32, f32)> {
+    let offset = origin - center;
+    let b = dot(&offset, direction);
+    let c = dot(&offset, &offset) - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    Some((-b - root, -b + root))
+}
@@ -0,0 +1,53 @@
+use nalgebra_glm::{Vec3, dot, normalize, smoothstep};
+use fastnoise_lite::FastNoiseLite;
+
+// Capa de nubes: una esfera algo mayor que el planeta que gira a su propia velocidad.
+// La cobertura se muestrea con ruido 3D sobre la esfera, así el patrón no tiene costuras
+#[derive(Debug, Clone, Copy)]
+pub struct CloudLayer {
+    pub center: Vec3,
+    pub radius: f32,
+    // Giro actual de la capa alrededor del eje Y (radianes)
+    pub rotation: f32,
+    // Escala del ruido sobre la esfera unidad; más alta = nubes más pequeñas
+    pub noise_scale: f32,
+    // Valor del ruido a partir del cual empieza a haber nubes y ancho de la transición
+    // hasta la cobertura completa (el ruido FBm rara vez pasa de ±0.5)
+    pub threshold: f32,
+    pub softness: f32,
+    pub opacity: f32,
+}
+
+impl CloudLayer {
+    // Cobertura (0 a 1) en una dirección dada en coordenadas de la capa
+    pub fn coverage(&self, noise: &FastNoiseLite, local_direction: &Vec3) -> f32 {
+        let position = normalize(local_direction) * self.noise_scale;
+        let value = noise.get_noise_3d(position.x, position.y, position.z);
+        smoothstep(self.threshold, self.threshold + self.softness, value) * self.opacity
+    }
+
+    // Fracción de la luz del Sol que tapan las nubes sobre `point` (0 = sin sombra)
+    pub fn shadow(&self, noise: &FastNoiseLite, point: &Vec3, sun_direction: &Vec3) -> f32 {
+        // Punto donde el rayo hacia el Sol sale de la esfera de nubes
+        let offset = point - self.center;
+        let b = dot(&offset, sun_direction);
+        let c = dot(&offset, &offset) - self.radius * self.radius;
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return 0.0;
+        }
+        let t = -b + discriminant.sqrt();
+        if t <= 0.0 {
+            return 0.0;
+        }
+        let hit = point + sun_direction * t;
+        self.coverage(noise, &self.local_direction(&hit))
+    }
+
+    // Dirección desde el centro en coordenadas de la capa (deshace el giro en Y)
+    fn local_direction(&self, world_point: &Vec3) -> Vec3 {
+        let direction = world_point - self.center;
+        let (sin, cos) = self.rotation.sin_cos();
+        Vec3::new(cos * direction.x - sin * direction.z, direction.y, sin * direction.x + cos * direction.z)
+    }
+}
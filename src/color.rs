@@ -1,4 +1,5 @@
 use std::fmt;
+use nalgebra_glm::Vec3;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
@@ -36,7 +37,7 @@ impl Color {
   }
 
   // Function to return the color as a hex value
-  pub fn to_hex(&self) -> u32 {
+  pub fn to_hex(self) -> u32 {
     ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
   }
 
@@ -50,15 +51,18 @@ impl Color {
     }
   }
 
+  #[allow(dead_code)]
   pub fn is_black(&self) -> bool {
     self.r == 0 && self.g == 0 && self.b == 0 
   }
 
   // New blend mode methods
+  #[allow(dead_code)]
   pub fn blend_normal(&self, blend: &Color) -> Color {
     if blend.is_black() { *self } else { *blend }
   }
 
+  #[allow(dead_code)]
   pub fn blend_multiply(&self, blend: &Color) -> Color {
     Color::new(
       ((self.r as f32 * blend.r as f32) / 255.0) as u8,
@@ -67,6 +71,7 @@ impl Color {
     )
   }
 
+  #[allow(dead_code)]
   pub fn blend_add(&self, blend: &Color) -> Color {
     Color::new(
       (self.r as u16 + blend.r as u16).min(255) as u8,
@@ -75,14 +80,16 @@ impl Color {
     )
   }
 
+  #[allow(dead_code)]
   pub fn blend_subtract(&self, blend: &Color) -> Color {
-    let r = (self.r as i16 - blend.r as i16).max(0).min(255) as u8;
-    let g = (self.g as i16 - blend.g as i16).max(0).min(255) as u8;
-    let b = (self.b as i16 - blend.b as i16).max(0).min(255) as u8;
+    let r = (self.r as i16 - blend.r as i16).clamp(0, 255) as u8;
+    let g = (self.g as i16 - blend.g as i16).clamp(0, 255) as u8;
+    let b = (self.b as i16 - blend.b as i16).clamp(0, 255) as u8;
 
     Color::new(r, g, b)
   }
 
+  #[allow(dead_code)]
   pub fn blend_screen(&self, blend: &Color) -> Color {
     Color::new(
       255 - ((255 - self.r as u16) * (255 - blend.r as u16) / 255) as u8,
@@ -123,6 +130,19 @@ impl Mul<f32> for Color {
   }
 }
 
+// Per-channel multiplication, used to tint a color with the light that reaches it
+impl Mul<Vec3> for Color {
+  type Output = Color;
+
+  fn mul(self, light: Vec3) -> Color {
+    Color {
+      r: (self.r as f32 * light.x).clamp(0.0, 255.0) as u8,
+      g: (self.g as f32 * light.y).clamp(0.0, 255.0) as u8,
+      b: (self.b as f32 * light.z).clamp(0.0, 255.0) as u8,
+    }
+  }
+}
+
 // Implement display formatting for Color
 impl fmt::Display for Color {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
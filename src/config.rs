@@ -0,0 +1,210 @@
+use std::env;
+use crate::postprocess::PASS_NAMES;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnimationFormat {
+    None,
+    Gif,
+    Apng,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AntiAliasing {
+    None,
+    // Renderiza a k veces la resolución y reduce
+    Ssaa(u32),
+    // Varias muestras de cobertura y profundidad por píxel, sombreado una vez por píxel
+    Msaa(u32),
+}
+
+impl std::str::FromStr for AntiAliasing {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "none" => Ok(AntiAliasing::None),
+            "ssaa2" => Ok(AntiAliasing::Ssaa(2)),
+            "ssaa3" => Ok(AntiAliasing::Ssaa(3)),
+            "ssaa4" => Ok(AntiAliasing::Ssaa(4)),
+            "msaa2" => Ok(AntiAliasing::Msaa(2)),
+            "msaa4" => Ok(AntiAliasing::Msaa(4)),
+            "msaa8" => Ok(AntiAliasing::Msaa(8)),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShadowMode {
+    Off,
+    // Mapa de profundidad desde el Sol, sirve para cualquier geometría
+    Map,
+    // Eclipses calculados con las esferas y anillos de la escena, con penumbra suave
+    Analytic,
+}
+
+impl std::str::FromStr for ShadowMode {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "off" => Ok(ShadowMode::Off),
+            "map" => Ok(ShadowMode::Map),
+            "analytic" => Ok(ShadowMode::Analytic),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub planet: u32,
+    // Resolución de la ventana o de la imagen exportada
+    pub width: usize,
+    pub height: usize,
+    // Resolución interna del framebuffer relativa a la de salida
+    pub render_scale: f32,
+    pub anti_aliasing: AntiAliasing,
+    // Pasadas de post-procesado activas al iniciar
+    pub post_passes: Vec<String>,
+    pub shadows: ShadowMode,
+    // Corona y protuberancias alrededor del Sol
+    pub corona: bool,
+    // Margen relativo alrededor de los umbrales de nivel de detalle (0 = sin histéresis)
+    pub lod_hysteresis: f32,
+    // Cuando hay un número de frames se renderiza sin ventana
+    pub frames: Option<u32>,
+    pub start_time: u32,
+    pub time_step: u32,
+    pub fps: u32,
+    pub output_dir: String,
+    pub animation: AnimationFormat,
+    // Escribe los frames como video Y4M en stdout
+    pub y4m: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            planet: 1,
+            width: 800,
+            height: 600,
+            render_scale: 1.0,
+            anti_aliasing: AntiAliasing::None,
+            // El bloom está activo de entrada para que el Sol brille fuera de su silueta
+            post_passes: vec![String::from("bloom")],
+            shadows: ShadowMode::Analytic,
+            corona: true,
+            lod_hysteresis: 0.15,
+            frames: None,
+            start_time: 1,
+            time_step: 1,
+            fps: 30,
+            output_dir: String::from("frames"),
+            animation: AnimationFormat::None,
+            y4m: false,
+        }
+    }
+}
+
+impl Config {
+    pub fn from_args() -> Result<Self, String> {
+        Self::parse(env::args().skip(1))
+    }
+
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut config = Config::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--planet" => config.planet = parse_value(&arg, args.next())?,
+                "--width" => config.width = parse_value(&arg, args.next())?,
+                "--height" => config.height = parse_value(&arg, args.next())?,
+                "--scale" => config.render_scale = parse_value(&arg, args.next())?,
+                "--aa" => config.anti_aliasing = parse_value(&arg, args.next())?,
+                "--post" => config.post_passes = parse_passes(&arg, args.next())?,
+                "--shadows" => config.shadows = parse_value(&arg, args.next())?,
+                "--no-corona" => config.corona = false,
+                "--lod-hysteresis" => config.lod_hysteresis = parse_value(&arg, args.next())?,
+                "--frames" => config.frames = Some(parse_value(&arg, args.next())?),
+                "--start" => config.start_time = parse_value(&arg, args.next())?,
+                "--step" => config.time_step = parse_value(&arg, args.next())?,
+                "--fps" => config.fps = parse_value(&arg, args.next())?,
+                "--out" => config.output_dir = parse_value(&arg, args.next())?,
+                "--gif" => config.animation = AnimationFormat::Gif,
+                "--apng" => config.animation = AnimationFormat::Apng,
+                "--y4m" => config.y4m = true,
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        if config.width == 0 || config.height == 0 {
+            return Err(String::from("--width and --height must be greater than 0"));
+        }
+        if config.animation == AnimationFormat::Gif && (config.width > u16::MAX as usize || config.height > u16::MAX as usize) {
+            return Err(format!("--width and --height must be at most {} with --gif", u16::MAX));
+        }
+        if !config.render_scale.is_finite() || config.render_scale <= 0.0 {
+            return Err(String::from("--scale must be greater than 0"));
+        }
+        if !(0.0..1.0).contains(&config.lod_hysteresis) {
+            return Err(String::from("--lod-hysteresis must be between 0 and 1"));
+        }
+        if config.frames == Some(0) {
+            return Err(String::from("--frames must be greater than 0"));
+        }
+        if config.fps == 0 {
+            return Err(String::from("--fps must be greater than 0"));
+        }
+
+        Ok(config)
+    }
+
+    pub fn framebuffer_size(&self, width: usize, height: usize) -> (usize, usize) {
+        let scale = match self.anti_aliasing {
+            AntiAliasing::Ssaa(factor) => self.render_scale * factor as f32,
+            _ => self.render_scale,
+        };
+        (
+            ((width as f32 * scale).round() as usize).max(1),
+            ((height as f32 * scale).round() as usize).max(1),
+        )
+    }
+
+    pub fn msaa_samples(&self) -> usize {
+        match self.anti_aliasing {
+            AntiAliasing::Msaa(samples) => samples as usize,
+            _ => 1,
+        }
+    }
+
+    // Tiempo de la animación en el frame `frame`. Un video sin final (o un `--start` o
+    // `--step` grandes) pasa del máximo de u32, así que el tiempo da la vuelta
+    pub fn frame_time(&self, frame: u32) -> u32 {
+        frame.wrapping_mul(self.time_step).wrapping_add(self.start_time)
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.frames.is_some() || self.y4m
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("Missing value for {}", flag))?;
+    value.parse().map_err(|_| format!("Invalid value for {}: {}", flag, value))
+}
+
+fn parse_passes(flag: &str, value: Option<String>) -> Result<Vec<String>, String> {
+    let value: String = parse_value(flag, value)?;
+    value
+        .split(',')
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            if PASS_NAMES.contains(&name) {
+                Ok(name.to_string())
+            } else {
+                Err(format!("Unknown post-processing pass: {} (expected one of {})", name, PASS_NAMES.join(", ")))
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,200 @@
+use nalgebra_glm::{Vec3, cross, dot, normalize, smoothstep};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::f32::consts::{PI, TAU};
+
+// Celdas de latitud y longitud en las que se reparten los cráteres para no recorrerlos todos
+const LATITUDE_CELLS: usize = 18;
+const LONGITUDE_CELLS: usize = 36;
+
+// Cómo se reparten los impactos sobre un cuerpo. Los radios son ángulos desde el centro
+// del cuerpo (en radianes), así valen también como fracción de su radio
+#[derive(Debug, Clone, Copy)]
+pub struct CraterDistribution {
+    pub seed: u64,
+    pub count: usize,
+    pub min_radius: f32,
+    pub max_radius: f32,
+    // Exponente de la ley de potencias: el número de cráteres de radio mayor que r cae como r^-exponente
+    pub size_exponent: f32,
+    // Profundidad de cada cráter respecto a su radio
+    pub depth_ratio: f32,
+    // Fracción de cráteres (los más jóvenes) que conservan el material claro y los rayos
+    pub ray_fraction: f32,
+}
+
+// Un impacto: centro sobre la esfera unidad, tamaño y la base para medir ángulos a su alrededor
+#[derive(Debug, Clone, Copy)]
+struct Crater {
+    center: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    radius: f32,
+    depth: f32,
+    // Altura del pico central respecto a la profundidad (0 en los cráteres simples)
+    peak: f32,
+    // Brillo de los rayos (0 en los cráteres viejos) y fase de su dibujo
+    rays: f32,
+    ray_phase: f32,
+    // Coseno del ángulo hasta el que llega su influencia (borde, eyecta o rayos)
+    reach_cos: f32,
+}
+
+// Lo que aportan los cráteres en un punto de la superficie
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CraterSample {
+    // Altura en fracciones del radio del cuerpo, para desplazar la superficie
+    pub height: f32,
+    // Forma del relieve sin escalar: -1 en el fondo, positiva en bordes y picos
+    pub relief: f32,
+    // Cambio de albedo: positivo en el material expulsado y los rayos de los cráteres jóvenes
+    pub albedo: f32,
+}
+
+// Campo de cráteres generado con una semilla. Los cráteres se guardan de más viejo a más
+// joven y cada uno borra el relieve anterior dentro de su cuenco
+#[derive(Debug, Clone, Default)]
+pub struct CraterField {
+    craters: Vec<Crater>,
+    cells: Vec<Vec<u32>>,
+}
+
+impl CraterField {
+    pub fn generate(distribution: &CraterDistribution) -> Self {
+        let mut rng = StdRng::seed_from_u64(distribution.seed);
+        let alpha = distribution.size_exponent;
+        let (min_power, max_power) = (distribution.min_radius.powf(-alpha), distribution.max_radius.powf(-alpha));
+        let young = ((1.0 - distribution.ray_fraction) * distribution.count as f32) as usize;
+
+        let mut field = CraterField {
+            craters: Vec::with_capacity(distribution.count),
+            cells: vec![Vec::new(); LATITUDE_CELLS * LONGITUDE_CELLS],
+        };
+        for index in 0..distribution.count {
+            // Dirección uniforme sobre la esfera
+            let y: f32 = rng.gen_range(-1.0..1.0);
+            let angle: f32 = rng.gen_range(0.0..TAU);
+            let ring = (1.0 - y * y).sqrt();
+            let center = Vec3::new(ring * angle.cos(), y, ring * angle.sin());
+
+            // Ley de potencias truncada entre el radio mínimo y el máximo
+            let u: f32 = rng.gen();
+            let radius = (min_power - u * (min_power - max_power)).powf(-1.0 / alpha);
+
+            let reference = if y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+            let tangent = normalize(&cross(&center, &reference));
+            let bitangent = cross(&center, &tangent);
+
+            // Los cráteres grandes son complejos: fondo plano y pico central
+            let peak = if radius > distribution.max_radius * 0.3 { 0.35 } else { 0.0 };
+            let rays = if index >= young { rng.gen_range(0.6..1.0) } else { 0.0 };
+            let reach = if rays > 0.0 { (radius * 8.0).min(1.2) } else { radius * 1.5 };
+
+            field.insert(Crater {
+                center,
+                tangent,
+                bitangent,
+                radius,
+                depth: radius * distribution.depth_ratio,
+                peak,
+                rays,
+                ray_phase: rng.gen_range(0.0..TAU),
+                reach_cos: reach.cos(),
+            }, reach);
+        }
+        field
+    }
+
+    // Guarda el cráter en todas las celdas que toca su zona de influencia
+    fn insert(&mut self, crater: Crater, reach: f32) {
+        let index = self.craters.len() as u32;
+        let (latitude, longitude) = latitude_longitude(&crater.center);
+        let min_latitude = latitude - reach;
+        let max_latitude = latitude + reach;
+        let widest = min_latitude.abs().max(max_latitude.abs());
+        // Cerca de los polos la zona cubre todas las longitudes
+        let half_width = if widest < 1.45 { reach / widest.cos() } else { PI };
+
+        let (first_row, last_row) = (latitude_cell(min_latitude), latitude_cell(max_latitude));
+        let columns = ((2.0 * half_width / TAU * LONGITUDE_CELLS as f32).ceil() as usize + 1).min(LONGITUDE_CELLS);
+        let first_column = longitude_cell(longitude - half_width);
+        for row in first_row..=last_row {
+            for step in 0..columns {
+                let column = (first_column + step) % LONGITUDE_CELLS;
+                self.cells[row * LONGITUDE_CELLS + column].push(index);
+            }
+        }
+        self.craters.push(crater);
+    }
+
+    // Relieve y albedo de los cráteres en la dirección `direction` desde el centro del cuerpo
+    pub fn sample(&self, direction: &Vec3) -> CraterSample {
+        let mut sample = CraterSample::default();
+        if self.craters.is_empty() {
+            return sample;
+        }
+        let direction = normalize(direction);
+        let (latitude, longitude) = latitude_longitude(&direction);
+        let cell = latitude_cell(latitude) * LONGITUDE_CELLS + longitude_cell(longitude);
+
+        for &index in &self.cells[cell] {
+            let crater = &self.craters[index as usize];
+            let cos_distance = dot(&direction, &crater.center);
+            if cos_distance < crater.reach_cos {
+                continue;
+            }
+            let s = cos_distance.clamp(-1.0, 1.0).acos() / crater.radius;
+
+            // El cráter joven tapa lo que había dentro de su cuenco
+            let inside = 1.0 - smoothstep(0.8, 1.0, s);
+            let relief = crater_profile(s, crater.peak);
+            sample.relief = sample.relief * (1.0 - inside) + relief;
+            sample.height = sample.height * (1.0 - inside) + relief * crater.depth;
+
+            if crater.rays > 0.0 {
+                let angle = dot(&direction, &crater.bitangent).atan2(dot(&direction, &crater.tangent));
+                sample.albedo = sample.albedo * (1.0 - inside) + crater.rays * ejecta_albedo(s, angle, crater.ray_phase, crater.radius);
+            } else {
+                // Los fondos viejos están algo más oscuros
+                sample.albedo = sample.albedo * (1.0 - inside) - 0.1 * inside;
+            }
+        }
+        sample.relief = sample.relief.clamp(-1.0, 1.0);
+        sample.albedo = sample.albedo.clamp(-1.0, 1.0);
+        sample
+    }
+}
+
+// Perfil de un cráter según la distancia al centro relativa a su radio: cuenco hasta el borde
+// (s = 1), reborde levantado alrededor y, en los complejos, fondo plano con pico central
+fn crater_profile(s: f32, peak: f32) -> f32 {
+    let mut bowl = if s < 1.0 { s * s - 1.0 } else { 0.0 };
+    if peak > 0.0 {
+        bowl = bowl.max(-0.7) + peak * (-(s / 0.2).powi(2)).exp();
+    }
+    let rim = (1.0 - (s - 1.0).abs() / 0.3).max(0.0);
+    bowl + rim * rim * 0.3
+}
+
+// Material claro alrededor de un cráter joven: el manto de eyecta junto al borde y rayos
+// estrechos que se alejan varios radios en direcciones al azar
+fn ejecta_albedo(s: f32, angle: f32, phase: f32, radius: f32) -> f32 {
+    let blanket = if s < 1.0 { 0.5 } else { 0.8 * (-(s - 1.0) * 1.5).exp() };
+    let pattern = (0.5 + 0.5 * (angle * 7.0 + phase).sin())
+        * (0.5 + 0.5 * (angle * 13.0 + phase * 2.0).sin())
+        * (0.5 + 0.5 * (angle * 23.0 + phase * 3.0).sin());
+    let length = (1.2 / radius).min(8.0);
+    let rays = pattern.powf(1.5) * (1.0 - s / length).max(0.0).powi(2) * 1.5;
+    if s < 1.0 { blanket } else { blanket.max(rays) }
+}
+
+fn latitude_longitude(direction: &Vec3) -> (f32, f32) {
+    (direction.y.clamp(-1.0, 1.0).asin(), direction.z.atan2(direction.x))
+}
+
+fn latitude_cell(latitude: f32) -> usize {
+    (((latitude + PI / 2.0) / PI * LATITUDE_CELLS as f32) as usize).min(LATITUDE_CELLS - 1)
+}
+
+fn longitude_cell(longitude: f32) -> usize {
+    ((longitude.rem_euclid(TAU) / TAU * LONGITUDE_CELLS as f32) as usize).min(LONGITUDE_CELLS - 1)
+}
@@ -0,0 +1,116 @@
+use nalgebra_glm::Vec3;
+use crate::rings::RingProfile;
+use std::f32::consts::PI;
+
+// Sombras analíticas para esferas y anillos: más baratas que el mapa de sombras y con una
+// penumbra suave que depende del tamaño aparente del Sol visto desde cada punto
+
+#[derive(Debug, Clone, Copy)]
+pub struct SphereOccluder {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RingOccluder {
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    // Perfil radial que da la opacidad en cada radio (entre `inner_radius` y `outer_radius`)
+    pub profile: RingProfile,
+}
+
+pub struct Eclipses {
+    pub sun_position: Vec3,
+    pub sun_radius: f32,
+    pub spheres: Vec<SphereOccluder>,
+    pub rings: Vec<RingOccluder>,
+}
+
+impl Eclipses {
+    // Fracción del disco solar visible desde `point` (1 = totalmente iluminado)
+    pub fn visibility(&self, point: &Vec3) -> f32 {
+        self.sphere_visibility(point) * self.ring_visibility(point)
+    }
+
+    pub fn sphere_visibility(&self, point: &Vec3) -> f32 {
+        let to_sun = self.sun_position - point;
+        let sun_distance = to_sun.magnitude();
+        let sun_direction = to_sun / sun_distance;
+        let sun_angle = (self.sun_radius / sun_distance).min(1.0).asin();
+
+        let mut visible = 1.0;
+        for sphere in &self.spheres {
+            let to_occluder = sphere.center - point;
+            let distance = to_occluder.magnitude();
+            // El punto pertenece a esta misma esfera (o está dentro): no se hace sombra a sí misma
+            if distance <= sphere.radius * 1.1 {
+                continue;
+            }
+            let direction = to_occluder / distance;
+            let cos_separation = direction.dot(&sun_direction);
+            if cos_separation <= 0.0 || distance >= sun_distance {
+                continue;
+            }
+
+            let occluder_angle = (sphere.radius / distance).min(1.0).asin();
+            let separation = cos_separation.min(1.0).acos();
+            let covered = disc_overlap(sun_angle, occluder_angle, separation) / (PI * sun_angle * sun_angle);
+            visible *= 1.0 - covered.clamp(0.0, 1.0);
+        }
+        visible
+    }
+
+    pub fn ring_visibility(&self, point: &Vec3) -> f32 {
+        let to_sun = self.sun_position - point;
+        let sun_distance = to_sun.magnitude();
+        let sun_direction = to_sun / sun_distance;
+        let sun_angle = (self.sun_radius / sun_distance).min(1.0).asin();
+
+        let mut visible = 1.0;
+        for ring in &self.rings {
+            let denominator = sun_direction.dot(&ring.normal);
+            if denominator.abs() < 1e-4 {
+                continue;
+            }
+            // Distancia hasta el plano del anillo siguiendo el rayo hacia el Sol
+            let t = (ring.center - point).dot(&ring.normal) / denominator;
+            if t <= 1e-3 {
+                continue;
+            }
+
+            let hit = point + sun_direction * t;
+            let radius = (hit - ring.center).magnitude();
+            // Ancho de la penumbra: tamaño aparente del Sol proyectado a la distancia del anillo
+            let penumbra = (t * sun_angle.tan()).max(1e-4);
+            let coverage = smoothstep(ring.inner_radius - penumbra, ring.inner_radius + penumbra, radius)
+                * (1.0 - smoothstep(ring.outer_radius - penumbra, ring.outer_radius + penumbra, radius));
+            let u = (radius - ring.inner_radius) / (ring.outer_radius - ring.inner_radius);
+            let opacity = ring.profile.opacity(ring.profile.radius_at(u.clamp(0.0, 1.0)), denominator, None);
+            visible *= 1.0 - opacity * coverage;
+        }
+        visible
+    }
+}
+
+// Área de la intersección de dos discos de radios r1 y r2 con centros separados por d
+fn disc_overlap(r1: f32, r2: f32, d: f32) -> f32 {
+    if d >= r1 + r2 {
+        return 0.0;
+    }
+    if d <= (r1 - r2).abs() {
+        let r = r1.min(r2);
+        return PI * r * r;
+    }
+
+    let a1 = ((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1)).clamp(-1.0, 1.0).acos();
+    let a2 = ((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2)).clamp(-1.0, 1.0).acos();
+    let k = ((-d + r1 + r2) * (d + r1 - r2) * (d - r1 + r2) * (d + r1 + r2)).max(0.0).sqrt();
+    r1 * r1 * a1 + r2 * r2 * a2 - 0.5 * k
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
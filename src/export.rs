@@ -0,0 +1,123 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use crate::framebuffer::Framebuffer;
+
+// Guarda un frame como PNG (RGB de 8 bits)
+pub fn write_png(path: &Path, framebuffer: &Framebuffer) -> io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, framebuffer.width as u32, framebuffer.height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&framebuffer.to_rgb())?;
+    writer.finish()?;
+    Ok(())
+}
+
+// GIF guarda el ancho y el alto en 16 bits
+fn gif_dimension(value: usize) -> io::Result<u16> {
+    u16::try_from(value).map_err(|_| {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("GIF dimensions must be at most {} pixels", u16::MAX))
+    })
+}
+
+pub enum AnimationWriter {
+    Gif {
+        encoder: gif::Encoder<BufWriter<File>>,
+        delay: u16,
+    },
+    Apng(png::Writer<BufWriter<File>>),
+}
+
+impl AnimationWriter {
+    pub fn gif(path: &Path, width: usize, height: usize, fps: u32) -> io::Result<Self> {
+        let (width, height) = (gif_dimension(width)?, gif_dimension(height)?);
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = gif::Encoder::new(file, width, height, &[])
+            .map_err(io::Error::other)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(io::Error::other)?;
+
+        // GIF guarda el retraso en centésimas de segundo, así que se redondea al más cercano:
+        // a 30 fps quedan 3 cs por frame y la animación se reproduce a unos 33 fps
+        let delay = (100.0 / fps as f32).round().max(1.0) as u16;
+        Ok(AnimationWriter::Gif { encoder, delay })
+    }
+
+    pub fn apng(path: &Path, width: usize, height: usize, fps: u32, frames: u32) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(frames, 0)?;
+        // El retraso es 1/fps segundos; el denominador tiene 16 bits
+        encoder.set_frame_delay(1, u16::try_from(fps).unwrap_or(u16::MAX))?;
+
+        Ok(AnimationWriter::Apng(encoder.write_header()?))
+    }
+
+    pub fn add_frame(&mut self, framebuffer: &Framebuffer) -> io::Result<()> {
+        let rgb = framebuffer.to_rgb();
+        match self {
+            AnimationWriter::Gif { encoder, delay } => {
+                let mut frame = gif::Frame::from_rgb_speed(
+                    gif_dimension(framebuffer.width)?,
+                    gif_dimension(framebuffer.height)?,
+                    &rgb,
+                    10,
+                );
+                frame.delay = *delay;
+                encoder.write_frame(&frame).map_err(io::Error::other)
+            }
+            AnimationWriter::Apng(writer) => Ok(writer.write_image_data(&rgb)?),
+        }
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            // El encoder de GIF escribe el final del archivo al destruirse
+            AnimationWriter::Gif { .. } => Ok(()),
+            AnimationWriter::Apng(writer) => Ok(writer.finish()?),
+        }
+    }
+}
+
+// Escribe frames en formato YUV4MPEG2 (4:4:4), listo para enviarse a ffmpeg por un pipe
+pub struct Y4mWriter<W: Write> {
+    out: W,
+    plane: Vec<u8>,
+}
+
+impl<W: Write> Y4mWriter<W> {
+    pub fn new(mut out: W, width: usize, height: usize, fps: u32) -> io::Result<Self> {
+        writeln!(out, "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444", width, height, fps)?;
+        Ok(Y4mWriter {
+            out,
+            plane: Vec::with_capacity(width * height * 3),
+        })
+    }
+
+    pub fn write_frame(&mut self, framebuffer: &Framebuffer) -> io::Result<()> {
+        let pixels = framebuffer.buffer.len();
+        self.plane.clear();
+        self.plane.resize(pixels * 3, 0);
+
+        // RGB -> YCbCr BT.601 de rango limitado, en tres planos separados
+        let (luma, chroma) = self.plane.split_at_mut(pixels);
+        let (cb_plane, cr_plane) = chroma.split_at_mut(pixels);
+        for (i, pixel) in framebuffer.buffer.iter().enumerate() {
+            let r = ((pixel >> 16) & 0xFF) as f32 / 255.0;
+            let g = ((pixel >> 8) & 0xFF) as f32 / 255.0;
+            let b = (pixel & 0xFF) as f32 / 255.0;
+
+            luma[i] = (16.0 + 65.481 * r + 128.553 * g + 24.966 * b).round() as u8;
+            cb_plane[i] = (128.0 - 37.797 * r - 74.203 * g + 112.0 * b).round() as u8;
+            cr_plane[i] = (128.0 + 112.0 * r - 93.786 * g - 18.214 * b).round() as u8;
+        }
+
+        self.out.write_all(b"FRAME\n")?;
+        self.out.write_all(&self.plane)?;
+        self.out.flush()
+    }
+}
@@ -2,14 +2,23 @@
 use nalgebra_glm::{Vec3, Vec2};
 use crate::color::Color;
 
+#[derive(Clone, Copy)]
 pub struct Fragment {
     pub position: Vec2,
+    #[allow(dead_code)]
     pub color: Color,
     pub depth: f32,
-    pub normal: Vec3,
-    pub intensity: f32,
+    // Normal y posición en coordenadas de mundo
+    pub world_normal: Vec3,
+    pub world_position: Vec3,
+    // Dirección normalizada desde la cámara hasta el fragmento
+    pub view_dir: Vec3,
+    // Posición en coordenadas del modelo, para patrones que se mueven con el cuerpo
     pub vertex_position: Vec3,
     pub uv: Option<Vec2>,
+    // Máscara de las muestras de MSAA que cubre y gradiente de profundidad para llegar a ellas
+    pub coverage: u32,
+    pub depth_slope: Vec2,
 }
 
 impl Fragment {
@@ -18,7 +27,6 @@ impl Fragment {
         color: Color,
         depth: f32,
         normal: Vec3,
-        intensity: f32,
         vertex_position: Vec3,
         uv: Option<Vec2>
     ) -> Self {  
@@ -26,10 +34,13 @@ impl Fragment {
             position,
             color,
             depth,
-            normal,
-            intensity,
+            world_normal: normal,
+            world_position: vertex_position,
+            view_dir: Vec3::new(0.0, 0.0, -1.0),
             vertex_position,
-            uv
+            uv,
+            coverage: 1,
+            depth_slope: Vec2::new(0.0, 0.0),
         }
     }
 }
\ No newline at end of file
@@ -1,12 +1,33 @@
 // framebuffer.rs
+use nalgebra_glm::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendMode {
+    // Reemplaza el color y escribe la profundidad
+    Opaque,
+    // Suma el color a lo que ya hay (capas de luz como la atmósfera) sin escribir la profundidad
+    Additive,
+    // Mezcla con lo que ya hay según la opacidad actual (capas transparentes), sin escribir la profundidad
+    Alpha,
+}
 
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
     pub buffer: Vec<u32>,
     pub zbuffer: Vec<f32>,
+    // Luz emitida por cada píxel (Sol, zonas calientes), usada por el bloom
+    pub emissive: Vec<u32>,
+    // Posiciones de muestreo dentro del píxel (MSAA); con una sola muestra es el centro
+    pub sample_offsets: Vec<Vec2>,
+    sample_buffer: Vec<u32>,
+    sample_zbuffer: Vec<f32>,
+    sample_emissive: Vec<u32>,
     background_color: u32,
     current_color: u32,
+    current_emissive: u32,
+    blend_mode: BlendMode,
+    current_alpha: f32,
 }
 
 impl Framebuffer {
@@ -16,11 +37,46 @@ impl Framebuffer {
             height,
             buffer: vec![0; width * height],
             zbuffer: vec![f32::INFINITY; width * height],
+            emissive: vec![0; width * height],
+            sample_offsets: sample_pattern(1),
+            sample_buffer: Vec::new(),
+            sample_zbuffer: Vec::new(),
+            sample_emissive: Vec::new(),
             background_color: 0x000000,
             current_color: 0xFFFFFF,
+            current_emissive: 0x000000,
+            blend_mode: BlendMode::Opaque,
+            current_alpha: 1.0,
         }
     }
 
+    pub fn samples(&self) -> usize {
+        self.sample_offsets.len()
+    }
+
+    // Activa MSAA con el número de muestras por píxel dado (1, 2, 4 u 8)
+    pub fn set_samples(&mut self, samples: usize) {
+        self.sample_offsets = sample_pattern(samples);
+        self.allocate_samples();
+    }
+
+    fn allocate_samples(&mut self) {
+        let count = if self.samples() > 1 { self.width * self.height * self.samples() } else { 0 };
+        self.sample_buffer = vec![self.background_color; count];
+        self.sample_zbuffer = vec![f32::INFINITY; count];
+        self.sample_emissive = vec![0; count];
+    }
+
+    // Reasigna los buffers de color y profundidad para una nueva resolución
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.buffer = vec![self.background_color; width * height];
+        self.zbuffer = vec![f32::INFINITY; width * height];
+        self.emissive = vec![0; width * height];
+        self.allocate_samples();
+    }
+
     pub fn clear(&mut self) {
         for pixel in self.buffer.iter_mut() {
             *pixel = self.background_color;
@@ -28,18 +84,91 @@ impl Framebuffer {
         for depth in self.zbuffer.iter_mut() {
             *depth = f32::INFINITY;
         }
+        self.emissive.fill(0);
+        self.sample_buffer.fill(self.background_color);
+        self.sample_zbuffer.fill(f32::INFINITY);
+        self.sample_emissive.fill(0);
     }
 
     pub fn point(&mut self, x: usize, y: usize, depth: f32) {
         if x < self.width && y < self.height {
             let index = y * self.width + x;
             if self.zbuffer[index] > depth {
-                self.buffer[index] = self.current_color;
-                self.zbuffer[index] = depth;
+                match self.blend_mode {
+                    BlendMode::Opaque => {
+                        self.buffer[index] = self.current_color;
+                        self.emissive[index] = self.current_emissive;
+                        self.zbuffer[index] = depth;
+                    }
+                    BlendMode::Additive => {
+                        self.buffer[index] = add(self.buffer[index], self.current_color);
+                        self.emissive[index] = add(self.emissive[index], self.current_emissive);
+                    }
+                    BlendMode::Alpha => {
+                        self.buffer[index] = mix(self.buffer[index], self.current_color, self.current_alpha);
+                        self.emissive[index] = mix(self.emissive[index], self.current_emissive, self.current_alpha);
+                    }
+                }
+            }
+        }
+    }
+
+    // Escribe el color actual en las muestras cubiertas por `coverage` (un bit por muestra).
+    // La profundidad de cada muestra se extrapola desde el centro del píxel con la pendiente del triángulo
+    pub fn point_coverage(&mut self, x: usize, y: usize, depth: f32, depth_slope: Vec2, coverage: u32) {
+        let samples = self.samples();
+        if samples == 1 {
+            self.point(x, y, depth);
+            return;
+        }
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let base = (y * self.width + x) * samples;
+        for (s, offset) in self.sample_offsets.iter().enumerate() {
+            if coverage & (1 << s) == 0 {
+                continue;
+            }
+            let sample_depth = depth
+                + depth_slope.x * (offset.x - 0.5)
+                + depth_slope.y * (offset.y - 0.5);
+            let index = base + s;
+            if self.sample_zbuffer[index] > sample_depth {
+                match self.blend_mode {
+                    BlendMode::Opaque => {
+                        self.sample_buffer[index] = self.current_color;
+                        self.sample_emissive[index] = self.current_emissive;
+                        self.sample_zbuffer[index] = sample_depth;
+                    }
+                    BlendMode::Additive => {
+                        self.sample_buffer[index] = add(self.sample_buffer[index], self.current_color);
+                        self.sample_emissive[index] = add(self.sample_emissive[index], self.current_emissive);
+                    }
+                    BlendMode::Alpha => {
+                        self.sample_buffer[index] = mix(self.sample_buffer[index], self.current_color, self.current_alpha);
+                        self.sample_emissive[index] = mix(self.sample_emissive[index], self.current_emissive, self.current_alpha);
+                    }
+                }
             }
         }
     }
 
+    // Promedia las muestras de cada píxel en `buffer` y deja en `zbuffer` la más cercana
+    pub fn resolve_samples(&mut self) {
+        let samples = self.samples();
+        if samples == 1 {
+            return;
+        }
+
+        for index in 0..self.width * self.height {
+            let range = index * samples..(index + 1) * samples;
+            self.buffer[index] = average(self.sample_buffer[range.clone()].iter());
+            self.emissive[index] = average(self.sample_emissive[range.clone()].iter());
+            self.zbuffer[index] = self.sample_zbuffer[range].iter().fold(f32::INFINITY, |a, &b| a.min(b));
+        }
+    }
+
     pub fn set_background_color(&mut self, color: u32) {
         self.background_color = color;
     }
@@ -47,4 +176,102 @@ impl Framebuffer {
     pub fn set_current_color(&mut self, color: u32) {
         self.current_color = color;
     }
-}
\ No newline at end of file
+
+    pub fn set_current_emissive(&mut self, color: u32) {
+        self.current_emissive = color;
+    }
+
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    // Opacidad usada por BlendMode::Alpha (0 = transparente, 1 = opaco)
+    pub fn set_current_alpha(&mut self, alpha: f32) {
+        self.current_alpha = alpha.clamp(0.0, 1.0);
+    }
+
+    // Copia la imagen en `target` a su resolución: promedia los píxeles que caen
+    // en cada píxel destino (filtro de caja) o repite el más cercano al ampliar
+    pub fn resolve_into(&self, target: &mut Framebuffer) {
+        if self.width == target.width && self.height == target.height {
+            target.buffer.copy_from_slice(&self.buffer);
+            target.emissive.copy_from_slice(&self.emissive);
+            return;
+        }
+
+        let scale_x = self.width as f32 / target.width as f32;
+        let scale_y = self.height as f32 / target.height as f32;
+
+        for ty in 0..target.height {
+            let y0 = ((ty as f32 * scale_y) as usize).min(self.height - 1);
+            let y1 = (((ty + 1) as f32 * scale_y).ceil() as usize).clamp(y0 + 1, self.height);
+            for tx in 0..target.width {
+                let x0 = ((tx as f32 * scale_x) as usize).min(self.width - 1);
+                let x1 = (((tx + 1) as f32 * scale_x).ceil() as usize).clamp(x0 + 1, self.width);
+
+                let footprint = (y0..y1).flat_map(|y| (x0..x1).map(move |x| y * self.width + x));
+                let index = ty * target.width + tx;
+                target.buffer[index] = average(footprint.clone().map(|i| &self.buffer[i]));
+                target.emissive[index] = average(footprint.map(|i| &self.emissive[i]));
+            }
+        }
+    }
+
+    // Convierte el buffer 0xRRGGBB a bytes RGB consecutivos
+    pub fn to_rgb(&self) -> Vec<u8> {
+        let mut rgb = Vec::with_capacity(self.buffer.len() * 3);
+        for pixel in &self.buffer {
+            rgb.push(((pixel >> 16) & 0xFF) as u8);
+            rgb.push(((pixel >> 8) & 0xFF) as u8);
+            rgb.push((pixel & 0xFF) as u8);
+        }
+        rgb
+    }
+}
+
+// Suma por canal de colores 0xRRGGBB, saturando en 255
+fn add(a: u32, b: u32) -> u32 {
+    let channel = |shift: u32| (((a >> shift) & 0xFF) + ((b >> shift) & 0xFF)).min(0xFF) << shift;
+    channel(16) | channel(8) | channel(0)
+}
+
+// Interpolación por canal de colores 0xRRGGBB: `b` con peso `alpha` sobre `a`
+fn mix(a: u32, b: u32, alpha: f32) -> u32 {
+    let channel = |shift: u32| {
+        let (from, to) = (((a >> shift) & 0xFF) as f32, ((b >> shift) & 0xFF) as f32);
+        ((from + (to - from) * alpha).round() as u32).min(0xFF) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
+
+// Promedio por canal de colores 0xRRGGBB
+fn average<'a>(pixels: impl Iterator<Item = &'a u32>) -> u32 {
+    let (mut r, mut g, mut b, mut count) = (0u32, 0u32, 0u32, 0u32);
+    for pixel in pixels {
+        r += (pixel >> 16) & 0xFF;
+        g += (pixel >> 8) & 0xFF;
+        b += pixel & 0xFF;
+        count += 1;
+    }
+    if count == 0 {
+        return 0;
+    }
+    ((r / count) << 16) | ((g / count) << 8) | (b / count)
+}
+
+// Patrones de muestreo estándar (D3D) en coordenadas del píxel [0, 1]
+fn sample_pattern(samples: usize) -> Vec<Vec2> {
+    let pattern: &[(f32, f32)] = match samples {
+        2 => &[(4.0, 4.0), (-4.0, -4.0)],
+        4 => &[(-2.0, -6.0), (6.0, -2.0), (-6.0, 2.0), (2.0, 6.0)],
+        8 => &[
+            (1.0, -3.0), (-1.0, 3.0), (5.0, 1.0), (-3.0, -5.0),
+            (-5.0, 5.0), (-7.0, -1.0), (3.0, 7.0), (7.0, -7.0),
+        ],
+        _ => &[(0.0, 0.0)],
+    };
+    pattern
+        .iter()
+        .map(|&(x, y)| Vec2::new(0.5 + x / 16.0, 0.5 + y / 16.0))
+        .collect()
+}
@@ -0,0 +1,110 @@
+use nalgebra_glm::{Vec3, dot, normalize};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Light {
+    // Luz que sale de una posición en todas direcciones (el Sol)
+    Point { position: Vec3, color: Vec3, intensity: f32 },
+    // Luz muy lejana que llega con la misma dirección a toda la escena; `direction` es hacia donde viaja
+    Directional { direction: Vec3, color: Vec3, intensity: f32 },
+    // Luz de fondo que llega por igual a todas las superficies
+    Ambient { color: Vec3, intensity: f32 },
+}
+
+impl Light {
+    // Dirección desde `point` hacia la luz (la ambiental no tiene dirección)
+    pub fn direction_from(&self, point: &Vec3) -> Option<Vec3> {
+        match self {
+            Light::Point { position, .. } => Some(normalize(&(position - point))),
+            Light::Directional { direction, .. } => Some(-normalize(direction)),
+            Light::Ambient { .. } => None,
+        }
+    }
+
+    pub fn radiance(&self) -> Vec3 {
+        match self {
+            Light::Point { color, intensity, .. }
+            | Light::Directional { color, intensity, .. }
+            | Light::Ambient { color, intensity } => color * *intensity,
+        }
+    }
+
+    // Solo las luces puntuales (el Sol) proyectan sombra en la escena
+    fn casts_shadow(&self) -> bool {
+        matches!(self, Light::Point { .. })
+    }
+}
+
+// Luces de la escena, compartidas por todos los shaders a través de los Uniforms
+#[derive(Debug, Clone)]
+pub struct Lighting {
+    pub lights: Vec<Light>,
+}
+
+impl Lighting {
+    pub fn ambient(&self) -> Vec3 {
+        self.lights
+            .iter()
+            .filter(|light| matches!(light, Light::Ambient { .. }))
+            .fold(Vec3::zeros(), |total, light| total + light.radiance())
+    }
+
+    // La primera luz que proyecta sombra es el Sol
+    fn sun(&self) -> Option<&Light> {
+        self.lights.iter().find(|light| light.casts_shadow())
+    }
+
+    // Dirección desde `point` hacia el Sol
+    pub fn sun_direction(&self, point: &Vec3) -> Vec3 {
+        self.sun()
+            .and_then(|light| light.direction_from(point))
+            .unwrap_or_else(|| Vec3::new(0.0, 1.0, 0.0))
+    }
+
+    pub fn sun_radiance(&self) -> Vec3 {
+        self.sun().map_or(Vec3::zeros(), |light| light.radiance())
+    }
+
+    // Suma de la luz difusa (Lambert) de las luces con dirección; `sun_visibility`
+    // atenúa las que proyectan sombra
+    pub fn diffuse(&self, point: &Vec3, normal: &Vec3, sun_visibility: f32) -> Vec3 {
+        self.directional_lights(point, sun_visibility)
+            .fold(Vec3::zeros(), |total, (light, direction, visibility)| {
+                total + light.radiance() * lambert(normal, &direction) * visibility
+            })
+    }
+
+    // Brillo especular (Blinn-Phong) de las luces con dirección
+    pub fn specular(&self, point: &Vec3, normal: &Vec3, view_dir: &Vec3, shininess: f32, sun_visibility: f32) -> Vec3 {
+        self.directional_lights(point, sun_visibility)
+            .fold(Vec3::zeros(), |total, (light, direction, visibility)| {
+                total + light.radiance() * blinn_phong(normal, &direction, view_dir, shininess) * visibility
+            })
+    }
+
+    fn directional_lights<'a>(&'a self, point: &'a Vec3, sun_visibility: f32) -> impl Iterator<Item = (&'a Light, Vec3, f32)> {
+        self.lights.iter().filter_map(move |light| {
+            let direction = light.direction_from(point)?;
+            let visibility = if light.casts_shadow() { sun_visibility } else { 1.0 };
+            Some((light, direction, visibility))
+        })
+    }
+}
+
+pub fn lambert(normal: &Vec3, light_dir: &Vec3) -> f32 {
+    dot(normal, light_dir).max(0.0)
+}
+
+pub fn blinn_phong(normal: &Vec3, light_dir: &Vec3, view_dir: &Vec3, shininess: f32) -> f32 {
+    if dot(normal, light_dir) <= 0.0 {
+        return 0.0;
+    }
+    let half_vector = normalize(&(light_dir + view_dir));
+    dot(normal, &half_vector).max(0.0).powf(shininess)
+}
+
+// Aproximación de Schlick: la reflexión crece hacia el borde del disco, donde la
+// normal queda perpendicular a la vista
+pub fn fresnel(normal: &Vec3, view_dir: &Vec3, base_reflectance: f32, power: f32) -> f32 {
+    let cos_theta = dot(normal, view_dir).clamp(0.0, 1.0);
+    base_reflectance + (1.0 - base_reflectance) * (1.0 - cos_theta).powf(power)
+}
@@ -1,28 +1,59 @@
-use nalgebra_glm::{Vec3, Mat4, look_at, perspective};
-use minifb::{Key, Window, WindowOptions};
+use nalgebra_glm::{Vec3, Vec4, Mat4, look_at, perspective};
+use minifb::{Key, KeyRepeat, Window, WindowOptions, ScaleMode};
 use std::f32::consts::PI;
+use std::fs;
+use std::io;
+use std::path::Path;
 use crate::color::Color;
-use crate::fragment::Fragment;
-use fastnoise_lite::{FastNoiseLite, NoiseType, FractalType};
+use fastnoise_lite::{FastNoiseLite, NoiseType, FractalType, CellularDistanceFunction, CellularReturnType};
 
 mod framebuffer;
 mod triangle;
 mod vertex;
+// Las mallas de la escena se generan en mesh.rs; el cargador de OBJ queda para modelos externos
+#[allow(dead_code)]
 mod obj;
 mod color;
 mod fragment;
 mod shaders;
 mod camera;
-
-use framebuffer::Framebuffer;
+mod config;
+mod export;
+mod postprocess;
+mod shadow;
+mod eclipse;
+mod scene;
+mod light;
+mod atmosphere;
+mod clouds;
+mod terrain;
+mod craters;
+mod seasons;
+mod rings;
+mod mesh;
+
+use framebuffer::{Framebuffer, BlendMode};
 use vertex::Vertex;
-use obj::Obj;
 use camera::Camera;
 use triangle::triangle;
-use shaders::{vertex_shader,sun_shader, fragment_shader, time_based_color_cycling_shader, mars_shader_wrapper, earth_shader_wrapper
-    ,jupiter_shader_wrapper,mercury_shader_wrapper, uranus_shader_wrapper,saturn_shader_wrapper, saturn_ring_shader,moon_shader_wrapper};
-
-pub struct Uniforms {
+use config::{Config, AnimationFormat, ShadowMode};
+use export::{AnimationWriter, Y4mWriter};
+use postprocess::PostChain;
+use shadow::ShadowMap;
+use eclipse::{Eclipses, SphereOccluder, RingOccluder};
+use light::{Light, Lighting};
+use atmosphere::Atmosphere;
+use clouds::CloudLayer;
+use terrain::{Terrain, TerrainNoise};
+use seasons::{Orbit, PolarCaps};
+use mesh::{Meshes, LodLevel, LodState};
+use rings::{RingProfile, SATURN_RINGS, URANUS_RINGS, NEPTUNE_RINGS};
+use scene::{Scene, Body, Mesh, RING_MESH_RADII, SPHERE_MESH_RADIUS};
+use shaders::{vertex_shader,sun_shader, sun_emission_shader, time_based_color_cycling_shader, mars_shader_wrapper, earth_shader_wrapper, earth_emission_shader
+    ,jupiter_shader_wrapper,mercury_shader_wrapper, uranus_shader_wrapper,saturn_shader_wrapper, neptune_shader_wrapper, moon_shader_wrapper
+    ,venus_shader_wrapper, venus_cloud_shader};
+
+pub struct Uniforms<'a> {
     model_matrix: Mat4,
     view_matrix: Mat4,
     projection_matrix: Mat4,
@@ -30,26 +61,37 @@ pub struct Uniforms {
     time: u32,
     noise: FastNoiseLite,
     cloud_noise: FastNoiseLite, 
+    shadow_map: Option<ShadowMap>,
+    eclipses: Eclipses,
+    lighting: Lighting,
+    camera_position: Vec3,
+    atmosphere: Option<Atmosphere>,
+    cloud_layer: Option<CloudLayer>,
+    terrain: Option<&'static Terrain>,
+    terrain_noise: &'a TerrainNoise,
+    rings: Option<RingProfile>,
+    ring_radii: (f32, f32),
+    polar_caps: Option<PolarCaps>,
 }
-// Noises ---------------------------------------------------------------------------------------------------------
-fn create_noise() -> FastNoiseLite {
-    //create_cloud_noise() 
-    // create_cell_noise()
-    // create_ground_noise()
-    create_sun_noise()
+
+// Lo que se genera una vez al iniciar y comparten todos los frames
+struct Assets {
+    meshes: Meshes,
+    terrain_noise: TerrainNoise,
 }
 
+const SHADOW_MAP_SIZE: usize = 1024;
+
+// Noises ---------------------------------------------------------------------------------------------------------
 fn create_sun_noise() -> FastNoiseLite {
+    // Celdas de Voronoi para la granulación: la diferencia entre las dos distancias más
+    // cercanas es mínima en los bordes entre celdas
     let mut noise = FastNoiseLite::with_seed(42);
-    
-    // Use FBm for multi-layered noise, giving a "turbulent" feel
-    noise.set_noise_type(Some(NoiseType::Perlin));  // Perlin noise for smooth, natural texture
-    noise.set_fractal_type(Some(FractalType::FBm)); // FBm for layered detail
-    noise.set_fractal_octaves(Some(10));             // High octaves for rich detail
-    noise.set_fractal_lacunarity(Some(2.0));        // Higher lacunarity = more contrast between layers
-    noise.set_fractal_gain(Some(0.5));              // Higher gain = more influence of smaller details
-    noise.set_frequency(Some(0.02));                // Low frequency = large features
-    
+    noise.set_noise_type(Some(NoiseType::Cellular));
+    noise.set_cellular_distance_function(Some(CellularDistanceFunction::Euclidean));
+    noise.set_cellular_return_type(Some(CellularReturnType::Distance2Sub));
+    noise.set_cellular_jitter(Some(0.9));
+    noise.set_frequency(Some(1.0));
     noise
 }
 fn create_earth_noise() -> FastNoiseLite {
@@ -128,6 +170,27 @@ fn create_saturn_noise() -> FastNoiseLite {
     noise.set_frequency(Some(0.02));               // Scale of gas bands
     noise
 }
+fn create_neptune_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(2089);
+    noise.set_noise_type(Some(NoiseType::Perlin));
+    noise.set_fractal_type(Some(FractalType::FBm));
+    noise.set_fractal_octaves(Some(5));
+    noise.set_fractal_lacunarity(Some(2.0));
+    noise.set_fractal_gain(Some(0.5));
+    noise.set_frequency(Some(0.008));
+    noise
+}
+fn create_venus_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(4707);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise.set_fractal_type(Some(FractalType::FBm));
+    noise.set_fractal_octaves(Some(4));
+    noise.set_fractal_lacunarity(Some(2.0));
+    noise.set_fractal_gain(Some(0.5));
+    noise.set_frequency(Some(0.01));
+    noise
+}
+#[allow(dead_code)]
 fn create_moon_noise() -> FastNoiseLite {
     let mut noise = FastNoiseLite::with_seed(4321);
     noise.set_noise_type(Some(NoiseType::OpenSimplex2));
@@ -139,7 +202,7 @@ fn create_moon_noise() -> FastNoiseLite {
     noise
 }
 // View ------------------------------------------------------------------------------------------------------------
-fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3, aspect_ratio: f32) -> Mat4 {
+fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3, _aspect_ratio: f32) -> Mat4 {
     let (sin_x, cos_x) = rotation.x.sin_cos();
     let (sin_y, cos_y) = rotation.y.sin_cos();
     let (sin_z, cos_z) = rotation.z.sin_cos();
@@ -199,8 +262,25 @@ fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
         0.0, 0.0, 0.0, 1.0
     )
 }
+// Radio aproximado en píxeles de la esfera que envuelve al cuerpo
+fn screen_radius(body: &Body, eye: Vec3, projection_matrix: &Mat4, height: f32) -> f32 {
+    let distance = (body.translation - eye).magnitude();
+    let radius = body.radius();
+    if distance <= radius {
+        return f32::INFINITY;
+    }
+    radius / (distance * distance - radius * radius).sqrt() * projection_matrix[(1, 1)] * height / 2.0
+}
 // Renders ------------------------------------------------------------------------------------------------------------------------------------------------
-fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], planet_shader: fn(&Fragment, &Uniforms) -> Color) {
+fn render(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    vertex_array: &[Vertex],
+    body: &Body,
+) {
+    framebuffer.set_blend_mode(body.blend);
+    framebuffer.set_current_alpha(1.0);
+
     let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
     for vertex in vertex_array {
         let transformed = vertex_shader(vertex, uniforms);
@@ -220,60 +300,499 @@ fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Ve
 
     let mut fragments = Vec::new();
     for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
+        fragments.extend(triangle(&tri[0], &tri[1], &tri[2], &framebuffer.sample_offsets));
     }
 
     for fragment in fragments {
         let x = fragment.position.x as usize;
         let y = fragment.position.y as usize;
         if x < framebuffer.width && y < framebuffer.height {
-            let color = planet_shader(&fragment, &uniforms);
+            if let Some(alpha_shader) = body.alpha_shader {
+                let alpha = alpha_shader(&fragment, uniforms);
+                if alpha <= 0.0 {
+                    continue;
+                }
+                framebuffer.set_current_alpha(alpha);
+            }
+            let color = (body.shader)(&fragment, uniforms);
+            let emissive = body.emission_shader.map_or(Color::black(), |shader| shader(&fragment, uniforms));
             framebuffer.set_current_color(color.to_hex());
-            framebuffer.point(x, y, fragment.depth);
+            framebuffer.set_current_emissive(emissive.to_hex());
+            framebuffer.point_coverage(x, y, fragment.depth, fragment.depth_slope, fragment.coverage);
+        }
+    }
+}
+
+// Cuerpos visibles para el planeta seleccionado: el planeta y, según el caso, su luna o sus anillos
+fn build_scene(current_planet: u32, time: u32, corona: bool) -> Scene {
+    let planet_shader = match current_planet {
+        1 => sun_shader,
+        2 => mercury_shader_wrapper,
+        3 => venus_shader_wrapper,
+        4 => earth_shader_wrapper,
+        5 => mars_shader_wrapper,
+        6 => jupiter_shader_wrapper,
+        7 => saturn_shader_wrapper,
+        8 => uranus_shader_wrapper,
+        9 => neptune_shader_wrapper,
+        _ => time_based_color_cycling_shader,
+    };
+    // Inclinación del eje de Urano y Neptuno, con el polo sur hacia la cámara como los vio la Voyager 2
+    let axial_tilt = match current_planet {
+        8 => -97.77_f32.to_radians(),
+        9 => -28.32_f32.to_radians(),
+        _ => 0.0,
+    };
+    // Venus gira en sentido retrógrado y muy despacio (un día sidéreo dura 243 días terrestres)
+    let spin = match current_planet {
+        3 => -(time as f32) * 0.0005,
+        _ => 0.0,
+    };
+    let mut planet = Body::new(Mesh::Sphere, Vec3::new(0.0, 0.0, 0.0), 2.0, Vec3::new(axial_tilt, spin, 0.0), planet_shader);
+    if current_planet == 1 {
+        planet.emission_shader = Some(sun_emission_shader);
+        planet.casts_shadow = false;
+    }
+    if current_planet == 4 {
+        planet.emission_shader = Some(earth_emission_shader);
+    }
+    match current_planet {
+        2 => planet.set_terrain(Terrain::mercury()),
+        5 => planet.set_terrain(Terrain::mars()),
+        _ => (),
+    }
+    // Casquetes polares que crecen y se encogen con las estaciones
+    planet.polar_caps = match current_planet {
+        4 => Some(PolarCaps::earth(&Orbit::earth(), time as f32)),
+        5 => Some(PolarCaps::mars(&Orbit::mars(), time as f32)),
+        _ => None,
+    };
+    let mut bodies = vec![planet];
+
+    if current_planet == 4 {
+        // Calcular la posición de la luna
+        let moon_scale = 0.35; // Escala de la luna respecto a la Tierra
+        let moon_distance = 1.8; // Distancia de la luna a la Tierra
+        let moon_orbit_speed = 0.5; // Velocidad orbital de la luna
+
+        let moon_angle = time as f32 * moon_orbit_speed;
+        let moon_x = moon_distance * moon_angle.cos();
+        let moon_z = moon_distance * moon_angle.sin();
+
+        let moon_translation = Vec3::new(moon_x, 0.0, moon_z);
+        let mut moon = Body::new(Mesh::Sphere, moon_translation, moon_scale, Vec3::new(0.0, 0.0, 0.0), moon_shader_wrapper);
+        moon.set_terrain(Terrain::moon());
+        bodies.push(moon);
+    }
+    // Anillos en el plano del ecuador; se mezclan con transparencia según su profundidad óptica
+    let rings = match current_planet {
+        7 => Some(SATURN_RINGS),
+        8 => Some(URANUS_RINGS),
+        9 => Some(NEPTUNE_RINGS),
+        _ => None,
+    };
+    if let Some(profile) = rings {
+        let ring_system = Body::ring_system(&bodies[0], profile);
+        bodies.push(ring_system);
+    }
+
+    // Nubes de la Tierra: giran más rápido que la superficie
+    let cloud_layer = match current_planet {
+        4 => Some(CloudLayer {
+            center: bodies[0].translation,
+            radius: bodies[0].radius() * 1.03,
+            rotation: time as f32 * 0.01,
+            noise_scale: 400.0,
+            threshold: 0.0,
+            softness: 0.25,
+            opacity: 0.9,
+        }),
+        // Venus: una capa casi opaca que da la vuelta en unos 4 días, 60 veces más rápido
+        // que la superficie y en el mismo sentido retrógrado (superrotación)
+        3 => Some(CloudLayer {
+            center: bodies[0].translation,
+            radius: bodies[0].radius() * 1.04,
+            rotation: -(time as f32) * 0.03,
+            noise_scale: 300.0,
+            threshold: -1.0,
+            softness: 0.3,
+            opacity: 1.0,
+        }),
+        _ => None,
+    };
+    if let Some(layer) = &cloud_layer {
+        let mut clouds = Body::cloud_layer(&bodies[0], layer);
+        if current_planet == 3 {
+            clouds.shader = venus_cloud_shader;
+        }
+        bodies.push(clouds);
+    }
+
+    // Corona del Sol, opcional
+    if current_planet == 1 && corona {
+        let corona = Body::corona(&bodies[0]);
+        bodies.push(corona);
+    }
+
+    // Capa de atmósfera alrededor del planeta, se suma sobre la superficie
+    let atmosphere = match current_planet {
+        3 => Some(Atmosphere::venus(bodies[0].radius())),
+        4 => Some(Atmosphere::earth(bodies[0].radius())),
+        6 | 7 => Some(Atmosphere::gas_giant(bodies[0].radius())),
+        8 | 9 => Some(Atmosphere::ice_giant(bodies[0].radius())),
+        _ => None,
+    };
+    if let Some(atmosphere) = atmosphere {
+        let shell = Body::atmosphere_shell(&bodies[0], atmosphere);
+        bodies.push(shell);
+    }
+
+    // El Sol es la luz principal; la ambiental evita que el lado nocturno quede negro y una
+    // luz direccional tenue y azulada simula la luz reflejada que llega desde el lado opuesto
+    let sun_position = Vec3::new(-10.0, 4.0, 10.0);
+    let lighting = Lighting {
+        lights: vec![
+            Light::Point { position: sun_position, color: Vec3::new(1.0, 0.97, 0.9), intensity: 1.0 },
+            Light::Directional { direction: sun_position, color: Vec3::new(0.6, 0.7, 1.0), intensity: 0.05 },
+            Light::Ambient { color: Vec3::new(1.0, 1.0, 1.0), intensity: 0.1 },
+        ],
+    };
+
+    Scene {
+        sun_position,
+        sun_radius: 0.8,
+        lighting,
+        cloud_layer,
+        bodies,
+    }
+}
+
+// Esferas y anillos que proyectan sombra, en coordenadas de mundo
+fn build_eclipses(scene: &Scene, model_matrices: &[Mat4]) -> Eclipses {
+    let mut eclipses = Eclipses {
+        sun_position: scene.sun_position,
+        sun_radius: scene.sun_radius,
+        spheres: Vec::new(),
+        rings: Vec::new(),
+    };
+    for (body, model_matrix) in scene.bodies.iter().zip(model_matrices) {
+        match body.mesh {
+            Mesh::Sphere | Mesh::FineSphere if body.casts_shadow => eclipses.spheres.push(SphereOccluder {
+                center: body.translation,
+                radius: body.radius(),
+            }),
+            Mesh::Sphere | Mesh::FineSphere => (),
+            // Los anillos no entran en el mapa de sombras: su sombra siempre sale del perfil
+            // de profundidad óptica, así se ven los huecos y las zonas tenues
+            Mesh::Ring => {
+                let Some(profile) = body.rings else {
+                    continue;
+                };
+                eclipses.rings.push(RingOccluder {
+                    center: body.translation,
+                    normal: (model_matrix * Vec4::new(0.0, 1.0, 0.0, 0.0)).xyz().normalize(),
+                    inner_radius: profile.inner_radius() * body.scale,
+                    outer_radius: profile.outer_radius() * body.scale,
+                    profile,
+                })
+            }
+        }
+    }
+    eclipses
+}
+
+// Dibuja un frame del planeta seleccionado (con su luna o sus anillos) en el framebuffer
+fn render_frame(
+    framebuffer: &mut Framebuffer,
+    camera: &Camera,
+    assets: &Assets,
+    lod: &mut LodState,
+    config: &Config,
+    current_planet: u32,
+    time: u32,
+) {
+    let Assets { meshes, terrain_noise } = assets;
+    let shadows = config.shadows;
+    let scene = build_scene(current_planet, time, config.corona);
+
+    framebuffer.clear();
+    // Seleccionar el ruido correcto en función del planeta actual
+    let noise = match current_planet {
+        1 => create_sun_noise(),
+        2 => create_mercury_noise(),
+        3 => create_venus_noise(),
+        4 => create_earth_noise(),
+        5 => create_mars_noise(),
+        6 => create_jupiter_noise(),
+        7 => create_saturn_noise(),  // Add saturn
+        8 => create_uranus_noise(),
+        9 => create_neptune_noise(),
+        _ => FastNoiseLite::with_seed(0),
+    };
+    let width = framebuffer.width as f32;
+    let height = framebuffer.height as f32;
+    let aspect_ratio = width / height;
+    let view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
+    let projection_matrix = create_perspective_matrix(width, height);
+    let viewport_matrix = create_viewport_matrix(width, height);
+
+    let model_matrices: Vec<Mat4> = scene
+        .bodies
+        .iter()
+        .map(|body| create_model_matrix(body.translation, body.scale, body.rotation, aspect_ratio))
+        .collect();
+
+    // Nivel de detalle de cada cuerpo según el radio que ocupa en pantalla
+    let hysteresis = lod.hysteresis;
+    let previous_levels = lod.previous_levels(current_planet, scene.bodies.len());
+    let mut lod_levels = Vec::with_capacity(scene.bodies.len());
+    for (body, previous) in scene.bodies.iter().zip(previous_levels.iter_mut()) {
+        let radius = screen_radius(body, camera.eye, &projection_matrix, height);
+        let level = meshes.select_level(body.mesh, radius, *previous, hysteresis);
+        *previous = Some(level);
+        lod_levels.push(level);
+    }
+
+    // Pasada de profundidad desde el Sol con todos los cuerpos que proyectan sombra. El mapa
+    // cubre la esfera que los contiene; fuera de ella no hay nada que pueda tapar al Sol
+    let shadow_extent = scene
+        .bodies
+        .iter()
+        .filter(|body| body.casts_shadow)
+        .map(Body::bounding_radius)
+        .fold(0.0, f32::max);
+    let shadow_map = (shadows == ShadowMode::Map && shadow_extent > 0.0).then(|| {
+        let mut shadow_map = ShadowMap::new(SHADOW_MAP_SIZE, scene.sun_position, Vec3::new(0.0, 0.0, 0.0), shadow_extent);
+        for ((body, model_matrix), level) in scene.bodies.iter().zip(&model_matrices).zip(&lod_levels) {
+            if !body.casts_shadow {
+                continue;
+            }
+            let vertices = meshes.vertices(body.mesh, *level);
+            match body.terrain {
+                // El relieve se desplaza igual que en el vertex shader para que la sombra coincida
+                Some(terrain) => {
+                    let displaced: Vec<Vertex> = vertices
+                        .iter()
+                        .map(|vertex| Vertex {
+                            position: terrain.displace(terrain_noise, &vertex.position).0,
+                            ..vertex.clone()
+                        })
+                        .collect();
+                    shadow_map.render_caster(&displaced, model_matrix);
+                }
+                None => shadow_map.render_caster(vertices, model_matrix),
+            }
+        }
+        shadow_map
+    });
+    let mut eclipses = build_eclipses(&scene, &model_matrices);
+    match shadows {
+        ShadowMode::Analytic => (),
+        // Con el mapa de sombras las esferas ya están en el mapa; los anillos se siguen
+        // calculando con su perfil
+        ShadowMode::Map => eclipses.spheres.clear(),
+        ShadowMode::Off => {
+            eclipses.spheres.clear();
+            eclipses.rings.clear();
+        }
+    }
+
+    let mut uniforms = Uniforms { 
+        model_matrix: Mat4::identity(), 
+        view_matrix, 
+        projection_matrix, 
+        viewport_matrix, 
+        time, 
+        noise,
+        cloud_noise: create_cloud_noise(),
+        shadow_map,
+        eclipses,
+        lighting: scene.lighting.clone(),
+        camera_position: camera.eye,
+        atmosphere: None,
+        cloud_layer: scene.cloud_layer,
+        terrain: None,
+        terrain_noise,
+        rings: None,
+        ring_radii: meshes.ring_radii,
+        polar_caps: None,
+    };
+
+    // Primero los cuerpos opacos, así las capas transparentes y aditivas se prueban contra su profundidad
+    let mut draw_order: Vec<usize> = (0..scene.bodies.len()).collect();
+    draw_order.sort_by_key(|&index| match scene.bodies[index].blend {
+        BlendMode::Opaque => 0,
+        BlendMode::Alpha => 1,
+        BlendMode::Additive => 2,
+    });
+    for index in draw_order {
+        let body = &scene.bodies[index];
+        uniforms.model_matrix = model_matrices[index];
+        uniforms.atmosphere = body.atmosphere;
+        uniforms.terrain = body.terrain;
+        uniforms.rings = body.rings;
+        uniforms.polar_caps = body.polar_caps;
+        render(framebuffer, &uniforms, meshes.vertices(body.mesh, lod_levels[index]), body);
+    }
+    framebuffer.set_blend_mode(BlendMode::Opaque);
+
+    framebuffer.resolve_samples();
+}
+
+// Renderiza un número fijo de frames sin abrir ventana y los guarda en disco
+fn render_sequence(
+    config: &Config,
+    framebuffer: &mut Framebuffer,
+    camera: &Camera,
+    assets: &Assets,
+) -> io::Result<()> {
+    let frames = config.frames.unwrap_or(1);
+    let output_dir = Path::new(&config.output_dir);
+    fs::create_dir_all(output_dir)?;
+
+    let mut output = Framebuffer::new(config.width, config.height);
+    let post = PostChain::new(&config.post_passes);
+    let mut animation = match config.animation {
+        AnimationFormat::None => None,
+        AnimationFormat::Gif => Some(AnimationWriter::gif(
+            &output_dir.join("animation.gif"),
+            output.width,
+            output.height,
+            config.fps,
+        )?),
+        AnimationFormat::Apng => Some(AnimationWriter::apng(
+            &output_dir.join("animation.png"),
+            output.width,
+            output.height,
+            config.fps,
+            frames,
+        )?),
+    };
+
+    let mut lod = LodState::new(config.lod_hysteresis);
+    for frame in 0..frames {
+        let time = config.frame_time(frame);
+        render_frame(framebuffer, camera, assets, &mut lod, config, config.planet, time);
+        framebuffer.resolve_into(&mut output);
+        post.apply(&mut output);
+
+        export::write_png(&output_dir.join(format!("frame_{:04}.png", frame)), &output)?;
+        if let Some(animation) = animation.as_mut() {
+            animation.add_frame(&output)?;
         }
+        eprintln!("Frame {}/{}", frame + 1, frames);
     }
+
+    if let Some(animation) = animation {
+        animation.finish()?;
+    }
+    Ok(())
+}
+
+// Envía los frames como video Y4M por stdout; sin --frames sigue hasta que el lector cierra el pipe
+fn stream_y4m(
+    config: &Config,
+    framebuffer: &mut Framebuffer,
+    camera: &Camera,
+    assets: &Assets,
+) -> io::Result<()> {
+    let mut output = Framebuffer::new(config.width, config.height);
+    let post = PostChain::new(&config.post_passes);
+    let stdout = io::stdout().lock();
+    let mut writer = Y4mWriter::new(stdout, output.width, output.height, config.fps)?;
+
+    let mut lod = LodState::new(config.lod_hysteresis);
+    let mut frame = 0;
+    while config.frames.is_none_or(|frames| frame < frames) {
+        let time = config.frame_time(frame);
+        render_frame(framebuffer, camera, assets, &mut lod, config, config.planet, time);
+        framebuffer.resolve_into(&mut output);
+        post.apply(&mut output);
+
+        match writer.write_frame(&output) {
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => break,
+            result => result?,
+        }
+        frame = frame.wrapping_add(1);
+    }
+    Ok(())
 }
 
 // Main -------------------------------------------------------------------------------------------------------------------------------------
 fn main() {
-    let window_width = 800;
-    let window_height = 600;
-    let framebuffer_width = 800;
-    let framebuffer_height = 600;
+    let config = Config::from_args().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    });
+
+    let mut window_width = config.width;
+    let mut window_height = config.height;
+    let (framebuffer_width, framebuffer_height) = config.framebuffer_size(window_width, window_height);
 
     let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
+    framebuffer.set_background_color(0x000000);
+    framebuffer.set_samples(config.msaa_samples());
+
+    let mut camera = Camera::new(
+        Vec3::new(0.0, 0.0, 3.0), // Cámara más cercana
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0)
+    );
+    // Todas las mallas se generan con varios niveles de detalle; las icosferas son para los
+    // cuerpos con relieve, que necesitan triángulos más pequeños
+    let lod = |vertices, min_screen_radius| LodLevel { vertices, min_screen_radius };
+    let meshes = Meshes {
+        sphere: vec![
+            lod(mesh::uv_sphere(SPHERE_MESH_RADIUS, 16, 8), 0.0),
+            lod(mesh::uv_sphere(SPHERE_MESH_RADIUS, 32, 16), 40.0),
+            lod(mesh::uv_sphere(SPHERE_MESH_RADIUS, 48, 24), 120.0),
+            lod(mesh::uv_sphere(SPHERE_MESH_RADIUS, 96, 48), 300.0),
+        ],
+        fine_sphere: vec![
+            lod(mesh::icosphere(SPHERE_MESH_RADIUS, 3), 0.0),
+            lod(mesh::icosphere(SPHERE_MESH_RADIUS, 4), 50.0),
+            lod(mesh::icosphere(SPHERE_MESH_RADIUS, 5), 120.0),
+            lod(mesh::icosphere(SPHERE_MESH_RADIUS, 6), 400.0),
+        ],
+        ring: vec![
+            lod(mesh::annulus(RING_MESH_RADII.0, RING_MESH_RADII.1, 64), 0.0),
+            lod(mesh::annulus(RING_MESH_RADII.0, RING_MESH_RADII.1, 192), 150.0),
+        ],
+        ring_radii: RING_MESH_RADII,
+    };
+    let assets = Assets { meshes, terrain_noise: TerrainNoise::new() };
+
+    if config.y4m {
+        stream_y4m(&config, &mut framebuffer, &camera, &assets)
+            .expect("Failed to stream frames");
+        return;
+    }
+    if config.is_headless() {
+        render_sequence(&config, &mut framebuffer, &camera, &assets)
+            .expect("Failed to export frames");
+        return;
+    }
+
     let mut window = Window::new(
         "Rust Graphics - Planets Shaders",
         window_width,
         window_height,
-        WindowOptions::default(),
+        WindowOptions {
+            resize: true,
+            scale_mode: ScaleMode::Stretch,
+            ..WindowOptions::default()
+        },
     )
     .unwrap();
+    let mut output = Framebuffer::new(window_width, window_height);
+    let mut post = PostChain::new(&config.post_passes);
 
     window.set_position(500, 500);
     window.update();
 
-    framebuffer.set_background_color(0x000000);
-
-    let translation = Vec3::new(0.0, 0.0, 0.0);
-    let rotation = Vec3::new(0.0, 0.0, 0.0);
-    let scale = 2.0f32;
-
-    let mut camera = Camera::new(
-        Vec3::new(0.0, 0.0, 3.0), // Cámara más cercana
-        Vec3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 1.0, 0.0)
-    );
-    //planeta
-    let obj = Obj::load("assets/sphere.obj").expect("Failed to load obj");
-    let vertex_arrays = obj.get_vertex_array();
-    //Anillo 
-    let rings_obj = Obj::load("assets/ring.obj").expect("Failed to load rings.obj");
-    let rings_vertex_arrays = rings_obj.get_vertex_array();
-    println!("Rings loaded with {} vertices", rings_obj.get_vertex_array().len());
-
     let mut time = 0;
-    let mut current_planet = 1;
+    let mut current_planet = config.planet;
+    let mut lod = LodState::new(config.lod_hysteresis);
     while window.is_open() {
         if window.is_key_down(Key::Escape) {
             break;
@@ -289,92 +808,47 @@ fn main() {
             Some(Key::Key5) => current_planet = 5,
             Some(Key::Key6) => current_planet = 6,
             Some(Key::Key7) => current_planet = 7,
+            Some(Key::Key8) => current_planet = 8,
+            Some(Key::Key9) => current_planet = 9,
             _ => (),
         }
 
         handle_input(&window, &mut camera);
+        handle_post_toggles(&window, &mut post);
+
+        // Si la ventana cambió de tamaño se reasignan los buffers; las matrices de
+        // proyección y viewport se reconstruyen con el nuevo tamaño en render_frame
+        let (new_width, new_height) = window.get_size();
+        if (new_width, new_height) != (window_width, window_height) && new_width > 0 && new_height > 0 {
+            window_width = new_width;
+            window_height = new_height;
+            let (framebuffer_width, framebuffer_height) = config.framebuffer_size(window_width, window_height);
+            framebuffer.resize(framebuffer_width, framebuffer_height);
+            output.resize(window_width, window_height);
+        }
+
+        render_frame(&mut framebuffer, &camera, &assets, &mut lod, &config, current_planet, time);
+        framebuffer.resolve_into(&mut output);
+        post.apply(&mut output);
 
-        framebuffer.clear();
-        // Seleccionar el ruido correcto en función del planeta actual
-        let noise = match current_planet {
-            1 => create_sun_noise(),
-            2 => create_mars_noise(),
-            3 => create_earth_noise(),
-            4 => create_jupiter_noise(),
-            5 => create_mercury_noise(),
-            6 => create_uranus_noise(),
-            7 => create_saturn_noise(),  // Add saturn
-            _ => FastNoiseLite::with_seed(0),
-        };
-        let aspect_ratio = window_width as f32 / window_height as f32;
-        let model_matrix = create_model_matrix(translation, scale, rotation, aspect_ratio);
-        let view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
-        let projection_matrix = create_perspective_matrix(window_width as f32, window_height as f32);
-        let viewport_matrix = create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32);
-        let mut uniforms = Uniforms { 
-            model_matrix, 
-            view_matrix, 
-            projection_matrix, 
-            viewport_matrix, 
-            time, 
-            noise,
-            cloud_noise: create_cloud_noise()
-        };
-
-        let planet_shader = match current_planet {
-            1 => sun_shader,
-            2 => mars_shader_wrapper,
-            3 => earth_shader_wrapper,
-            4 => jupiter_shader_wrapper,
-            5 => mercury_shader_wrapper,
-            6 => uranus_shader_wrapper,
-            7 => saturn_shader_wrapper,
-            _ => time_based_color_cycling_shader,
-        };        
-            render(&mut framebuffer, &uniforms, &vertex_arrays, planet_shader);
-            if current_planet == 3 { 
-                // Calcular y renderizar la luna
-                let moon_scale = 0.35; // Escala de la luna respecto a la Tierra
-                let moon_distance = 1.8; // Distancia de la luna a la Tierra
-                let moon_orbit_speed = 0.5; // Velocidad orbital de la luna
-            
-                let moon_angle = time as f32 * moon_orbit_speed;
-                let moon_x = moon_distance * moon_angle.cos();
-                let moon_z = moon_distance * moon_angle.sin();
-            
-                let moon_translation = Vec3::new(moon_x, 0.0, moon_z);
-                let moon_model_matrix = create_model_matrix(
-                        moon_translation,
-                        moon_scale, 
-                        Vec3::new(0.0, 0.0, 0.0),
-                        aspect_ratio,
-                );
-                uniforms.model_matrix = moon_model_matrix;
-            
-                render(&mut framebuffer, &uniforms, &vertex_arrays, moon_shader_wrapper);
-            }
-            
-            else if current_planet == 7 {
-                let ring_translation = Vec3::new(0.0, 0.0, 0.1); // Mover anillos hacia adelante
-                uniforms.model_matrix = create_model_matrix(
-                    ring_translation, // Ajustar la posición para evitar solapamiento
-                    0.5,              // Escala ajustada para los anillos
-                    Vec3::new(0.0, PI / 2.0, 0.0), // Rotación sobre el eje Y
-                    aspect_ratio,
-                );
-            
-                render(&mut framebuffer, &uniforms, &rings_vertex_arrays, |fragment, uniforms| {
-                    shaders::saturn_ring_shader(fragment, uniforms)
-                });
-            
-            }
-            
         window
-            .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
+            .update_with_buffer(&output.buffer, output.width, output.height)
             .unwrap();
     }
 }
 
+// F1..F6 activan o desactivan cada pasada de post-procesado
+fn handle_post_toggles(window: &Window, post: &mut PostChain) {
+    let keys = [Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6];
+    for (index, key) in keys.iter().enumerate() {
+        if window.is_key_pressed(*key, KeyRepeat::No) {
+            if let Some((name, enabled)) = post.toggle(index) {
+                eprintln!("{}: {}", name, if enabled { "on" } else { "off" });
+            }
+        }
+    }
+}
+
 fn handle_input(window: &Window, camera: &mut Camera) {
     let movement_speed = 1.0;
     let rotation_speed = PI / 50.0;
@@ -0,0 +1,212 @@
+use nalgebra_glm::{Vec2, Vec3, normalize};
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+use crate::vertex::Vertex;
+use crate::scene::Mesh;
+
+// Una versión de la malla y el radio en pantalla (en píxeles) a partir del cual se usa
+pub struct LodLevel {
+    pub vertices: Vec<Vertex>,
+    pub min_screen_radius: f32,
+}
+
+// Niveles de detalle de cada malla que pueden usar los cuerpos de la escena, de menos a más triángulos
+pub struct Meshes {
+    pub sphere: Vec<LodLevel>,
+    pub fine_sphere: Vec<LodLevel>,
+    pub ring: Vec<LodLevel>,
+    // Radios interior y exterior con los que se generaron los anillos: la u de la malla va de uno a otro
+    pub ring_radii: (f32, f32),
+}
+
+impl Meshes {
+    fn levels(&self, mesh: Mesh) -> &[LodLevel] {
+        match mesh {
+            Mesh::Sphere => &self.sphere,
+            Mesh::FineSphere => &self.fine_sphere,
+            Mesh::Ring => &self.ring,
+        }
+    }
+
+    pub fn vertices(&self, mesh: Mesh, level: usize) -> &[Vertex] {
+        let levels = self.levels(mesh);
+        &levels[level.min(levels.len() - 1)].vertices
+    }
+
+    // Nivel para un cuerpo que ocupa `screen_radius` píxeles. Partiendo del nivel del frame
+    // anterior solo se cambia cuando el radio pasa el umbral con un margen de `hysteresis`
+    // (fracción del umbral), así un cuerpo en el límite no alterna de malla en cada frame
+    pub fn select_level(&self, mesh: Mesh, screen_radius: f32, previous: Option<usize>, hysteresis: f32) -> usize {
+        let levels = self.levels(mesh);
+        let Some(previous) = previous else {
+            return levels.iter().rposition(|level| screen_radius >= level.min_screen_radius).unwrap_or(0);
+        };
+        let mut level = previous.min(levels.len() - 1);
+        while level + 1 < levels.len() && screen_radius > levels[level + 1].min_screen_radius * (1.0 + hysteresis) {
+            level += 1;
+        }
+        while level > 0 && screen_radius < levels[level].min_screen_radius * (1.0 - hysteresis) {
+            level -= 1;
+        }
+        level
+    }
+}
+
+// Nivel elegido para cada cuerpo en el frame anterior. Se olvida al cambiar de planeta,
+// porque la lista de cuerpos de la escena es otra
+pub struct LodState {
+    pub hysteresis: f32,
+    planet: u32,
+    levels: Vec<Option<usize>>,
+}
+
+impl LodState {
+    pub fn new(hysteresis: f32) -> Self {
+        LodState { hysteresis, planet: 0, levels: Vec::new() }
+    }
+
+    pub fn previous_levels(&mut self, planet: u32, bodies: usize) -> &mut [Option<usize>] {
+        if self.planet != planet || self.levels.len() != bodies {
+            self.planet = planet;
+            self.levels = vec![None; bodies];
+        }
+        &mut self.levels
+    }
+}
+
+// Esfera de meridianos y paralelos. `segments` es el número de divisiones alrededor del eje Y
+// y `rings` de polo a polo. Devuelve una lista de triángulos (tres vértices por triángulo)
+pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Vec<Vertex> {
+    let segments = segments.max(3);
+    let rings = rings.max(2);
+    let point = |segment: u32, ring: u32| {
+        let u = segment as f32 / segments as f32;
+        let v = ring as f32 / rings as f32;
+        let (sin_theta, cos_theta) = (v * PI).sin_cos();
+        let (sin_phi, cos_phi) = (u * TAU).sin_cos();
+        let direction = Vec3::new(sin_theta * cos_phi, cos_theta, sin_theta * sin_phi);
+        sphere_vertex(direction, radius, Vec2::new(u, v))
+    };
+
+    let mut vertices = Vec::with_capacity((segments * rings * 6) as usize);
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let top_left = point(segment, ring);
+            let top_right = point(segment + 1, ring);
+            let bottom_left = point(segment, ring + 1);
+            let bottom_right = point(segment + 1, ring + 1);
+            // En los polos uno de los dos triángulos del cuadrilátero no tiene área
+            if ring != 0 {
+                vertices.extend([top_left, bottom_left.clone(), top_right.clone()]);
+            }
+            if ring != rings - 1 {
+                vertices.extend([top_right, bottom_left, bottom_right]);
+            }
+        }
+    }
+    vertices
+}
+
+// Icosaedro subdividido `subdivisions` veces con los puntos proyectados sobre la esfera.
+// Los triángulos tienen casi el mismo tamaño en toda la superficie, sin acumularse en los polos
+pub fn icosphere(radius: f32, subdivisions: u32) -> Vec<Vertex> {
+    let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+    let mut points: Vec<Vec3> = [
+        (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+        (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+        (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y, z)| normalize(&Vec3::new(x, y, z)))
+    .collect();
+    let mut faces: Vec<[usize; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Las aristas compartidas reutilizan el mismo punto medio
+        let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut midpoint = |a: usize, b: usize, points: &mut Vec<Vec3>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                points.push(normalize(&(points[a] + points[b])));
+                points.len() - 1
+            })
+        };
+        faces = faces
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let ab = midpoint(a, b, &mut points);
+                let bc = midpoint(b, c, &mut points);
+                let ca = midpoint(c, a, &mut points);
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let mut vertices = Vec::with_capacity(faces.len() * 3);
+    for face in faces {
+        let directions = face.map(|index| points[index]);
+        let mut uvs = directions.map(|direction| spherical_uv(&direction));
+        // Los triángulos que cruzan la costura (u = 0 = 1) se cierran por el lado corto
+        let max_u = uvs.iter().fold(0.0_f32, |max, uv| max.max(uv.x));
+        if uvs.iter().any(|uv| max_u - uv.x > 0.5) {
+            for uv in uvs.iter_mut().filter(|uv| uv.x < 0.5) {
+                uv.x += 1.0;
+            }
+        }
+        for (direction, uv) in directions.into_iter().zip(uvs) {
+            vertices.push(sphere_vertex(direction, radius, uv));
+        }
+    }
+    vertices
+}
+
+// Anillo plano en el plano XZ entre `inner_radius` y `outer_radius`, con `segments` divisiones
+// alrededor del eje Y. u es el radio normalizado (0 en el borde interior, 1 en el exterior)
+// y v el ángulo, así los shaders de anillos pueden leer la estructura radial directamente
+pub fn annulus(inner_radius: f32, outer_radius: f32, segments: u32) -> Vec<Vertex> {
+    let segments = segments.max(3);
+    let normal = Vec3::new(0.0, 1.0, 0.0);
+    let point = |segment: u32, u: f32| {
+        let v = segment as f32 / segments as f32;
+        let (sin_phi, cos_phi) = (v * TAU).sin_cos();
+        let direction = Vec3::new(cos_phi, 0.0, sin_phi);
+        let radius = inner_radius + (outer_radius - inner_radius) * u;
+        Vertex {
+            tangent: direction,
+            ..Vertex::new(direction * radius, normal, Vec2::new(u, v))
+        }
+    };
+
+    let mut vertices = Vec::with_capacity((segments * 6) as usize);
+    for segment in 0..segments {
+        let inner = point(segment, 0.0);
+        let outer = point(segment, 1.0);
+        let next_inner = point(segment + 1, 0.0);
+        let next_outer = point(segment + 1, 1.0);
+        vertices.extend([inner, outer.clone(), next_inner.clone()]);
+        vertices.extend([next_inner, outer, next_outer]);
+    }
+    vertices
+}
+
+// Coordenadas de textura con la misma convención que `uv_sphere`: u da la vuelta al eje Y
+// y v va de 0 en el polo superior a 1 en el inferior
+fn spherical_uv(direction: &Vec3) -> Vec2 {
+    let u = direction.z.atan2(direction.x).rem_euclid(TAU) / TAU;
+    let v = direction.y.clamp(-1.0, 1.0).acos() / PI;
+    Vec2::new(u, v)
+}
+
+// Vértice de una esfera centrada en el origen: la normal es la propia dirección y la
+// tangente apunta hacia donde crece u (a lo largo del paralelo)
+fn sphere_vertex(direction: Vec3, radius: f32, tex_coords: Vec2) -> Vertex {
+    let (sin_phi, cos_phi) = (tex_coords.x * TAU).sin_cos();
+    Vertex {
+        tangent: Vec3::new(-sin_phi, 0.0, cos_phi),
+        ..Vertex::new(direction * radius, direction, tex_coords)
+    }
+}
@@ -0,0 +1,461 @@
+use nalgebra_glm::Vec3;
+use crate::framebuffer::Framebuffer;
+
+// Nombres de las pasadas en el orden en que se aplican (usados por --post y las teclas F1..F6)
+pub const PASS_NAMES: [&str; 6] = ["bloom", "grading", "gamma", "fxaa", "chromatic", "vignette"];
+
+// Imagen en punto flotante (0.0 a 1.0 por canal) sobre la que trabajan las pasadas
+#[derive(Clone)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Vec3>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![Vec3::zeros(); width * height],
+        }
+    }
+
+    pub fn from_pixels(width: usize, height: usize, pixels: &[u32]) -> Self {
+        Image {
+            width,
+            height,
+            pixels: pixels.iter().map(|&pixel| unpack(pixel)).collect(),
+        }
+    }
+
+    pub fn write_to(&self, framebuffer: &mut Framebuffer) {
+        for (target, pixel) in framebuffer.buffer.iter_mut().zip(&self.pixels) {
+            *target = pack(pixel);
+        }
+    }
+
+    // Lectura con las coordenadas limitadas al borde de la imagen
+    pub fn get(&self, x: i32, y: i32) -> Vec3 {
+        let x = x.clamp(0, self.width as i32 - 1) as usize;
+        let y = y.clamp(0, self.height as i32 - 1) as usize;
+        self.pixels[y * self.width + x]
+    }
+
+    // Muestreo bilineal en coordenadas de píxel (los centros están en +0.5)
+    pub fn sample(&self, x: f32, y: f32) -> Vec3 {
+        let x = x - 0.5;
+        let y = y - 0.5;
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let tx = x - x0;
+        let ty = y - y0;
+        let (x0, y0) = (x0 as i32, y0 as i32);
+
+        let top = self.get(x0, y0).lerp(&self.get(x0 + 1, y0), tx);
+        let bottom = self.get(x0, y0 + 1).lerp(&self.get(x0 + 1, y0 + 1), tx);
+        top.lerp(&bottom, ty)
+    }
+}
+
+pub fn unpack(pixel: u32) -> Vec3 {
+    Vec3::new(
+        ((pixel >> 16) & 0xFF) as f32 / 255.0,
+        ((pixel >> 8) & 0xFF) as f32 / 255.0,
+        (pixel & 0xFF) as f32 / 255.0,
+    )
+}
+
+pub fn pack(color: &Vec3) -> u32 {
+    let r = (color.x.clamp(0.0, 1.0) * 255.0).round() as u32;
+    let g = (color.y.clamp(0.0, 1.0) * 255.0).round() as u32;
+    let b = (color.z.clamp(0.0, 1.0) * 255.0).round() as u32;
+    (r << 16) | (g << 8) | b
+}
+
+fn luma(color: &Vec3) -> f32 {
+    color.dot(&Vec3::new(0.299, 0.587, 0.114))
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// `emissive` es la luz emitida por cada píxel según los shaders; la mayoría de las pasadas la ignoran
+pub trait PostPass {
+    fn apply(&self, image: &mut Image, emissive: &Image);
+}
+
+// Cadena de pasadas a pantalla completa que se pueden activar y desactivar en tiempo de ejecución
+pub struct PostChain {
+    passes: Vec<(&'static str, Box<dyn PostPass>, bool)>,
+}
+
+impl PostChain {
+    pub fn new(enabled: &[String]) -> Self {
+        let passes: Vec<Box<dyn PostPass>> = vec![
+            Box::new(Bloom::default()),
+            Box::new(ColorGrading::default()),
+            Box::new(Gamma::default()),
+            Box::new(Fxaa::default()),
+            Box::new(ChromaticAberration::default()),
+            Box::new(Vignette::default()),
+        ];
+
+        PostChain {
+            passes: PASS_NAMES
+                .iter()
+                .zip(passes)
+                .map(|(&name, pass)| (name, pass, enabled.iter().any(|e| e == name)))
+                .collect(),
+        }
+    }
+
+    // Invierte el estado de la pasada `index` y devuelve su nombre y nuevo estado
+    pub fn toggle(&mut self, index: usize) -> Option<(&'static str, bool)> {
+        let (name, _, enabled) = self.passes.get_mut(index)?;
+        *enabled = !*enabled;
+        Some((name, *enabled))
+    }
+
+    pub fn apply(&self, framebuffer: &mut Framebuffer) {
+        if self.passes.iter().all(|(_, _, enabled)| !enabled) {
+            return;
+        }
+
+        let mut image = Image::from_pixels(framebuffer.width, framebuffer.height, &framebuffer.buffer);
+        let emissive = Image::from_pixels(framebuffer.width, framebuffer.height, &framebuffer.emissive);
+        for (_, pass, enabled) in &self.passes {
+            if *enabled {
+                pass.apply(&mut image, &emissive);
+            }
+        }
+        image.write_to(framebuffer);
+    }
+}
+
+// Bloom: la luz emitida más las zonas que superan el umbral de brillo se difumina a
+// varias resoluciones (cada nivel a la mitad del anterior) y se suma a la imagen, de modo
+// que el brillo se extiende más allá de la silueta del cuerpo
+pub struct Bloom {
+    pub threshold: f32,
+    pub levels: usize,
+    pub radius: usize,
+    pub intensity: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom { threshold: 0.9, levels: 5, radius: 4, intensity: 3.0 }
+    }
+}
+
+impl PostPass for Bloom {
+    fn apply(&self, image: &mut Image, emissive: &Image) {
+        let mut level = Image::new(image.width, image.height);
+        for ((target, pixel), emitted) in level.pixels.iter_mut().zip(&image.pixels).zip(&emissive.pixels) {
+            let excess = (luma(pixel) - self.threshold).max(0.0) / (1.0 - self.threshold).max(1e-4);
+            *target = pixel * excess.min(1.0) + emitted;
+        }
+
+        let mut glow = Image::new(image.width, image.height);
+        let mut factor = 1.0;
+        for _ in 0..self.levels {
+            level = downsample(&level);
+            factor *= 2.0;
+            if level.width < 2 || level.height < 2 {
+                break;
+            }
+
+            let blurred = gaussian_blur(&level, self.radius);
+            for y in 0..glow.height {
+                for x in 0..glow.width {
+                    let sample = blurred.sample((x as f32 + 0.5) / factor, (y as f32 + 0.5) / factor);
+                    glow.pixels[y * glow.width + x] += sample;
+                }
+            }
+        }
+
+        let weight = self.intensity / self.levels as f32;
+        for (pixel, light) in image.pixels.iter_mut().zip(&glow.pixels) {
+            *pixel += light * weight;
+        }
+    }
+}
+
+// Reduce la imagen a la mitad promediando bloques de 2x2
+fn downsample(image: &Image) -> Image {
+    let mut result = Image::new(image.width.div_ceil(2), image.height.div_ceil(2));
+    for y in 0..result.height {
+        for x in 0..result.width {
+            let (sx, sy) = (2 * x as i32, 2 * y as i32);
+            result.pixels[y * result.width + x] = (image.get(sx, sy)
+                + image.get(sx + 1, sy)
+                + image.get(sx, sy + 1)
+                + image.get(sx + 1, sy + 1))
+                * 0.25;
+        }
+    }
+    result
+}
+
+// Desenfoque gaussiano separable (horizontal y luego vertical)
+pub fn gaussian_blur(image: &Image, radius: usize) -> Image {
+    let sigma = (radius as f32 / 2.0).max(0.5);
+    let weights: Vec<f32> = (0..=radius)
+        .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total = weights[0] + 2.0 * weights[1..].iter().sum::<f32>();
+    let weights: Vec<f32> = weights.iter().map(|w| w / total).collect();
+
+    let blur = |source: &Image, dx: i32, dy: i32| {
+        let mut result = Image::new(source.width, source.height);
+        for y in 0..source.height as i32 {
+            for x in 0..source.width as i32 {
+                let mut sum = source.get(x, y) * weights[0];
+                for (i, weight) in weights.iter().enumerate().skip(1) {
+                    let i = i as i32;
+                    sum += (source.get(x + dx * i, y + dy * i) + source.get(x - dx * i, y - dy * i)) * *weight;
+                }
+                result.pixels[y as usize * source.width + x as usize] = sum;
+            }
+        }
+        result
+    };
+
+    blur(&blur(image, 1, 0), 0, 1)
+}
+
+// Ajustes de color: exposición, contraste, saturación y un tinte multiplicativo
+pub struct ColorGrading {
+    pub exposure: f32,
+    pub contrast: f32,
+    pub saturation: f32,
+    pub tint: Vec3,
+}
+
+impl Default for ColorGrading {
+    fn default() -> Self {
+        ColorGrading {
+            exposure: 1.1,
+            contrast: 1.1,
+            saturation: 1.15,
+            tint: Vec3::new(1.0, 0.98, 0.95),
+        }
+    }
+}
+
+impl PostPass for ColorGrading {
+    fn apply(&self, image: &mut Image, _emissive: &Image) {
+        for pixel in image.pixels.iter_mut() {
+            let exposed = pixel.component_mul(&self.tint) * self.exposure;
+            let gray = Vec3::repeat(luma(&exposed));
+            let saturated = gray.lerp(&exposed, self.saturation);
+            *pixel = (saturated - Vec3::repeat(0.5)) * self.contrast + Vec3::repeat(0.5);
+        }
+    }
+}
+
+pub struct Gamma {
+    pub gamma: f32,
+}
+
+impl Default for Gamma {
+    fn default() -> Self {
+        Gamma { gamma: 2.2 }
+    }
+}
+
+impl PostPass for Gamma {
+    fn apply(&self, image: &mut Image, _emissive: &Image) {
+        let exponent = 1.0 / self.gamma;
+        for pixel in image.pixels.iter_mut() {
+            *pixel = pixel.map(|c| c.max(0.0).powf(exponent));
+        }
+    }
+}
+
+// FXAA: detecta bordes por contraste de luminancia y mezcla a lo largo de ellos
+pub struct Fxaa {
+    pub edge_threshold: f32,
+    pub edge_threshold_min: f32,
+    pub subpixel: f32,
+    pub search_steps: usize,
+}
+
+impl Default for Fxaa {
+    fn default() -> Self {
+        Fxaa {
+            edge_threshold: 0.125,
+            edge_threshold_min: 0.0312,
+            subpixel: 0.75,
+            search_steps: 8,
+        }
+    }
+}
+
+impl PostPass for Fxaa {
+    fn apply(&self, image: &mut Image, _emissive: &Image) {
+        let source = image.clone();
+        let luma_at = |x: i32, y: i32| luma(&source.get(x, y));
+        let luma_sample = |x: f32, y: f32| luma(&source.sample(x, y));
+
+        for y in 0..source.height as i32 {
+            for x in 0..source.width as i32 {
+                let m = luma_at(x, y);
+                let n = luma_at(x, y - 1);
+                let s = luma_at(x, y + 1);
+                let e = luma_at(x + 1, y);
+                let w = luma_at(x - 1, y);
+
+                let max = m.max(n).max(s).max(e).max(w);
+                let min = m.min(n).min(s).min(e).min(w);
+                let range = max - min;
+                if range < self.edge_threshold_min.max(max * self.edge_threshold) {
+                    continue;
+                }
+
+                let nw = luma_at(x - 1, y - 1);
+                let ne = luma_at(x + 1, y - 1);
+                let sw = luma_at(x - 1, y + 1);
+                let se = luma_at(x + 1, y + 1);
+
+                // Mezcla de subpíxel según cuánto difiere el centro del promedio vecino
+                let average = (2.0 * (n + s + e + w) + nw + ne + sw + se) / 12.0;
+                let subpixel_blend = smoothstep(0.0, 1.0, ((average - m).abs() / range).clamp(0.0, 1.0));
+                let subpixel_blend = subpixel_blend * subpixel_blend * self.subpixel;
+
+                let horizontal = (n + s - 2.0 * m).abs() * 2.0 + (ne + se - 2.0 * e).abs() + (nw + sw - 2.0 * w).abs()
+                    >= (e + w - 2.0 * m).abs() * 2.0 + (ne + nw - 2.0 * n).abs() + (se + sw - 2.0 * s).abs();
+
+                // Lado del borde con el mayor gradiente
+                let (luma_positive, luma_negative) = if horizontal { (s, n) } else { (e, w) };
+                let gradient_positive = (luma_positive - m).abs();
+                let gradient_negative = (luma_negative - m).abs();
+                let (step, luma_side, gradient) = if gradient_positive >= gradient_negative {
+                    (1.0, luma_positive, gradient_positive)
+                } else {
+                    (-1.0, luma_negative, gradient_negative)
+                };
+
+                let center_x = x as f32 + 0.5;
+                let center_y = y as f32 + 0.5;
+                let (edge_x, edge_y, dir_x, dir_y) = if horizontal {
+                    (center_x, center_y + step * 0.5, 1.0, 0.0)
+                } else {
+                    (center_x + step * 0.5, center_y, 0.0, 1.0)
+                };
+
+                // Busca los extremos del borde en ambas direcciones
+                let edge_luma = (m + luma_side) * 0.5;
+                let scaled_gradient = gradient * 0.25;
+                let mut distance_negative = self.search_steps as f32;
+                let mut distance_positive = self.search_steps as f32;
+                let mut end_luma_negative = edge_luma;
+                let mut end_luma_positive = edge_luma;
+                for i in 1..=self.search_steps {
+                    let d = i as f32;
+                    let value = luma_sample(edge_x - dir_x * d, edge_y - dir_y * d) - edge_luma;
+                    if value.abs() >= scaled_gradient {
+                        distance_negative = d;
+                        end_luma_negative = value;
+                        break;
+                    }
+                }
+                for i in 1..=self.search_steps {
+                    let d = i as f32;
+                    let value = luma_sample(edge_x + dir_x * d, edge_y + dir_y * d) - edge_luma;
+                    if value.abs() >= scaled_gradient {
+                        distance_positive = d;
+                        end_luma_positive = value;
+                        break;
+                    }
+                }
+
+                let edge_length = distance_negative + distance_positive;
+                let (closest, end_luma) = if distance_negative < distance_positive {
+                    (distance_negative, end_luma_negative)
+                } else {
+                    (distance_positive, end_luma_positive)
+                };
+                // Solo se mezcla si el extremo más cercano va en sentido contrario al centro
+                let center_is_smaller = m - edge_luma < 0.0;
+                let edge_blend = if (end_luma < 0.0) != center_is_smaller {
+                    0.5 - closest / edge_length
+                } else {
+                    0.0
+                };
+
+                let blend = edge_blend.max(subpixel_blend);
+                let (offset_x, offset_y) = if horizontal { (0.0, step * blend) } else { (step * blend, 0.0) };
+                image.pixels[y as usize * source.width + x as usize] =
+                    source.sample(center_x + offset_x, center_y + offset_y);
+            }
+        }
+    }
+}
+
+// Aberración cromática: separa los canales rojo y azul hacia los bordes de la imagen
+pub struct ChromaticAberration {
+    pub strength: f32,
+}
+
+impl Default for ChromaticAberration {
+    fn default() -> Self {
+        ChromaticAberration { strength: 0.004 }
+    }
+}
+
+impl PostPass for ChromaticAberration {
+    fn apply(&self, image: &mut Image, _emissive: &Image) {
+        let source = image.clone();
+        let center_x = source.width as f32 * 0.5;
+        let center_y = source.height as f32 * 0.5;
+
+        for y in 0..source.height {
+            for x in 0..source.width {
+                let px = x as f32 + 0.5;
+                let py = y as f32 + 0.5;
+                let dx = (px - center_x) * self.strength;
+                let dy = (py - center_y) * self.strength;
+
+                let red = source.sample(px + dx, py + dy).x;
+                let blue = source.sample(px - dx, py - dy).z;
+                let pixel = &mut image.pixels[y * source.width + x];
+                pixel.x = red;
+                pixel.z = blue;
+            }
+        }
+    }
+}
+
+// Viñeta: oscurece la imagen hacia las esquinas
+pub struct Vignette {
+    pub radius: f32,
+    pub softness: f32,
+    pub strength: f32,
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Vignette { radius: 0.75, softness: 0.45, strength: 0.6 }
+    }
+}
+
+impl PostPass for Vignette {
+    fn apply(&self, image: &mut Image, _emissive: &Image) {
+        let half_width = image.width as f32 * 0.5;
+        let half_height = image.height as f32 * 0.5;
+
+        for y in 0..image.height {
+            for x in 0..image.width {
+                let u = (x as f32 + 0.5 - half_width) / half_width;
+                let v = (y as f32 + 0.5 - half_height) / half_height;
+                let distance = (u * u + v * v).sqrt() / std::f32::consts::SQRT_2;
+                let shade = smoothstep(self.radius - self.softness, self.radius + self.softness, distance);
+                image.pixels[y * image.width + x] *= 1.0 - shade * self.strength;
+            }
+        }
+    }
+}
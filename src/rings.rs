@@ -0,0 +1,127 @@
+use nalgebra_glm::Vec3;
+use fastnoise_lite::FastNoiseLite;
+
+// Anillo principal con su profundidad óptica (τ) y color medio. Los radios están en radios del planeta
+#[derive(Debug, Clone, Copy)]
+pub struct RingBand {
+    #[allow(dead_code)]
+    pub name: &'static str,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub optical_depth: f32,
+    pub color: Vec3,
+}
+
+// Hueco estrecho dentro de un anillo, abierto por una luna o una resonancia
+#[derive(Debug, Clone, Copy)]
+pub struct RingGap {
+    #[allow(dead_code)]
+    pub name: &'static str,
+    pub radius: f32,
+    pub width: f32,
+}
+
+// Perfil radial de un sistema de anillos: bandas ordenadas de dentro hacia fuera (entre
+// ellas no hay material) y huecos dentro de las bandas
+#[derive(Debug, Clone, Copy)]
+pub struct RingProfile {
+    pub bands: &'static [RingBand],
+    pub gaps: &'static [RingGap],
+}
+
+// Saturno: el anillo C es tenue y oscuro, el B denso y brillante, la división de Cassini
+// casi vacía y el A intermedio, cortado por los huecos de Encke y Keeler
+pub const SATURN_RINGS: RingProfile = RingProfile {
+    bands: &[
+        RingBand { name: "C", inner_radius: 1.239, outer_radius: 1.527, optical_depth: 0.1, color: Vec3::new(0.55, 0.5, 0.45) },
+        RingBand { name: "B", inner_radius: 1.527, outer_radius: 1.951, optical_depth: 2.0, color: Vec3::new(0.9, 0.82, 0.68) },
+        RingBand { name: "Cassini", inner_radius: 1.951, outer_radius: 2.027, optical_depth: 0.08, color: Vec3::new(0.5, 0.47, 0.43) },
+        RingBand { name: "A", inner_radius: 2.027, outer_radius: 2.269, optical_depth: 0.5, color: Vec3::new(0.8, 0.74, 0.64) },
+    ],
+    gaps: &[
+        RingGap { name: "Maxwell", radius: 1.452, width: 0.0045 },
+        RingGap { name: "Huygens", radius: 1.955, width: 0.006 },
+        RingGap { name: "Encke", radius: 2.214, width: 0.0054 },
+        RingGap { name: "Keeler", radius: 2.265, width: 0.002 },
+    ],
+};
+
+impl RingProfile {
+    pub const fn inner_radius(&self) -> f32 {
+        self.bands[0].inner_radius
+    }
+
+    pub const fn outer_radius(&self) -> f32 {
+        self.bands[self.bands.len() - 1].outer_radius
+    }
+
+    // Radio correspondiente a la fracción `u` del ancho total (la u de la malla de anillos)
+    pub fn radius_at(&self, u: f32) -> f32 {
+        self.inner_radius() + (self.outer_radius() - self.inner_radius()) * u
+    }
+
+    pub fn band(&self, radius: f32) -> Option<&RingBand> {
+        self.bands
+            .iter()
+            .find(|band| radius >= band.inner_radius && radius < band.outer_radius)
+    }
+
+    // Profundidad óptica perpendicular al plano en `radius`. Con `noise` se añaden los
+    // anillos finos (ringlets) que se ven dentro de cada banda
+    pub fn optical_depth(&self, radius: f32, noise: Option<&FastNoiseLite>) -> f32 {
+        let Some(band) = self.band(radius) else {
+            return 0.0;
+        };
+        let mut depth = band.optical_depth;
+        if let Some(noise) = noise {
+            depth *= 1.0 + 0.5 * noise.get_noise_2d(radius * 2000.0, 0.0);
+        }
+        // Los huecos se abren con bordes suaves
+        for gap in self.gaps {
+            let half_width = gap.width * 0.5;
+            let distance = (radius - gap.radius).abs();
+            depth *= ((distance - half_width * 0.5) / (half_width * 0.5)).clamp(0.0, 1.0);
+        }
+        depth.max(0.0)
+    }
+
+    pub fn color(&self, radius: f32) -> Vec3 {
+        self.band(radius).map_or(Vec3::zeros(), |band| band.color)
+    }
+
+    // Fracción de luz que tapa el anillo en `radius` para un rayo que lo cruza con un ángulo
+    // cuyo coseno respecto a la normal es `cos_angle`: cuanto más rasante, más material atraviesa
+    pub fn opacity(&self, radius: f32, cos_angle: f32, noise: Option<&FastNoiseLite>) -> f32 {
+        let slant = cos_angle.abs().max(0.02);
+        1.0 - (-self.optical_depth(radius, noise) / slant).exp()
+    }
+}
+
+// Urano: anillos estrechos y oscuros; el ε es el más ancho y denso. Los anchos están
+// exagerados para que se vean con la resolución del render
+pub const URANUS_RINGS: RingProfile = RingProfile {
+    bands: &[
+        RingBand { name: "6", inner_radius: 1.634, outer_radius: 1.640, optical_depth: 0.3, color: Vec3::new(0.32, 0.31, 0.3) },
+        RingBand { name: "5", inner_radius: 1.649, outer_radius: 1.655, optical_depth: 0.4, color: Vec3::new(0.32, 0.31, 0.3) },
+        RingBand { name: "4", inner_radius: 1.663, outer_radius: 1.669, optical_depth: 0.3, color: Vec3::new(0.32, 0.31, 0.3) },
+        RingBand { name: "α", inner_radius: 1.746, outer_radius: 1.754, optical_depth: 0.4, color: Vec3::new(0.34, 0.33, 0.32) },
+        RingBand { name: "β", inner_radius: 1.783, outer_radius: 1.791, optical_depth: 0.3, color: Vec3::new(0.34, 0.33, 0.32) },
+        RingBand { name: "η", inner_radius: 1.843, outer_radius: 1.849, optical_depth: 0.2, color: Vec3::new(0.3, 0.3, 0.3) },
+        RingBand { name: "γ", inner_radius: 1.860, outer_radius: 1.866, optical_depth: 0.8, color: Vec3::new(0.34, 0.33, 0.32) },
+        RingBand { name: "δ", inner_radius: 1.886, outer_radius: 1.894, optical_depth: 0.5, color: Vec3::new(0.34, 0.33, 0.32) },
+        RingBand { name: "ε", inner_radius: 1.993, outer_radius: 2.009, optical_depth: 1.5, color: Vec3::new(0.38, 0.37, 0.36) },
+    ],
+    gaps: &[],
+};
+
+// Neptuno: anillos tenues y rojizos por el polvo; Lassell es una lámina ancha y casi transparente
+pub const NEPTUNE_RINGS: RingProfile = RingProfile {
+    bands: &[
+        RingBand { name: "Galle", inner_radius: 1.66, outer_radius: 1.72, optical_depth: 0.02, color: Vec3::new(0.4, 0.34, 0.3) },
+        RingBand { name: "Le Verrier", inner_radius: 2.146, outer_radius: 2.154, optical_depth: 0.2, color: Vec3::new(0.42, 0.36, 0.32) },
+        RingBand { name: "Lassell", inner_radius: 2.154, outer_radius: 2.306, optical_depth: 0.01, color: Vec3::new(0.4, 0.34, 0.3) },
+        RingBand { name: "Arago", inner_radius: 2.306, outer_radius: 2.314, optical_depth: 0.05, color: Vec3::new(0.4, 0.34, 0.3) },
+        RingBand { name: "Adams", inner_radius: 2.534, outer_radius: 2.546, optical_depth: 0.3, color: Vec3::new(0.45, 0.38, 0.34) },
+    ],
+    gaps: &[],
+};
@@ -0,0 +1,149 @@
+use nalgebra_glm::Vec3;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::Uniforms;
+use crate::light::Lighting;
+use crate::atmosphere::Atmosphere;
+use crate::framebuffer::BlendMode;
+use crate::clouds::CloudLayer;
+use crate::terrain::Terrain;
+use crate::rings::RingProfile;
+use crate::seasons::PolarCaps;
+use crate::shaders::{atmosphere_shader, cloud_shader, cloud_alpha_shader, corona_shader, ring_shader, ring_alpha_shader};
+
+pub type FragmentShader = fn(&Fragment, &Uniforms) -> Color;
+pub type AlphaShader = fn(&Fragment, &Uniforms) -> f32;
+
+// Radio de las esferas generadas (en unidades del modelo)
+pub const SPHERE_MESH_RADIUS: f32 = 0.5;
+// Radios interior y exterior de la malla de anillos, en radios del planeta. Cubre los
+// sistemas de anillos de Saturno, Urano y Neptuno; fuera de sus bandas el anillo es transparente
+pub const RING_MESH_RADII: (f32, f32) = (1.2, 2.6);
+// Radio de la esfera de la corona respecto al de la estrella
+pub const CORONA_EXTENT: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mesh {
+    Sphere,
+    // Esfera con muchos más triángulos, para los cuerpos con relieve
+    FineSphere,
+    Ring,
+}
+
+// Un cuerpo de la escena: qué malla usa, dónde está y con qué shaders se dibuja
+pub struct Body {
+    pub mesh: Mesh,
+    pub translation: Vec3,
+    pub scale: f32,
+    pub rotation: Vec3,
+    pub shader: FragmentShader,
+    pub emission_shader: Option<FragmentShader>,
+    pub casts_shadow: bool,
+    pub blend: BlendMode,
+    // Opacidad de cada fragmento cuando `blend` es BlendMode::Alpha
+    pub alpha_shader: Option<AlphaShader>,
+    // Parámetros de dispersión para las capas de atmósfera
+    pub atmosphere: Option<Atmosphere>,
+    // Relieve que desplaza los vértices en el vertex shader
+    pub terrain: Option<&'static Terrain>,
+    // Perfil radial de los anillos, para los cuerpos con malla de anillos
+    pub rings: Option<RingProfile>,
+    // Casquetes polares según la estación
+    pub polar_caps: Option<PolarCaps>,
+}
+
+impl Body {
+    pub fn new(mesh: Mesh, translation: Vec3, scale: f32, rotation: Vec3, shader: FragmentShader) -> Self {
+        Body {
+            mesh,
+            translation,
+            scale,
+            rotation,
+            shader,
+            emission_shader: None,
+            casts_shadow: true,
+            blend: BlendMode::Opaque,
+            alpha_shader: None,
+            atmosphere: None,
+            terrain: None,
+            rings: None,
+            polar_caps: None,
+        }
+    }
+
+    // El relieve necesita la esfera subdividida para que se note en la silueta
+    pub fn set_terrain(&mut self, terrain: &'static Terrain) {
+        self.mesh = Mesh::FineSphere;
+        self.terrain = Some(terrain);
+    }
+
+    // Esfera de nubes alrededor de `planet`, girada según `layer.rotation`. El giro es el de
+    // la capa en el mundo (el mismo que deshace `CloudLayer::shadow`), no relativo al planeta
+    pub fn cloud_layer(planet: &Body, layer: &CloudLayer) -> Self {
+        let scale = planet.scale * layer.radius / planet.radius();
+        let rotation = Vec3::new(planet.rotation.x, layer.rotation, planet.rotation.z);
+        Body {
+            casts_shadow: false,
+            blend: BlendMode::Alpha,
+            alpha_shader: Some(cloud_alpha_shader),
+            ..Body::new(Mesh::Sphere, planet.translation, scale, rotation, cloud_shader)
+        }
+    }
+
+    // Anillos en el plano del ecuador de `planet`. La malla está en radios del planeta. No se
+    // dibujan en el mapa de sombras, que los trataría como un disco opaco; su sombra la
+    // calculan los eclipses con el perfil
+    pub fn ring_system(planet: &Body, profile: RingProfile) -> Self {
+        Body {
+            casts_shadow: false,
+            blend: BlendMode::Alpha,
+            alpha_shader: Some(ring_alpha_shader),
+            rings: Some(profile),
+            ..Body::new(Mesh::Ring, planet.translation, planet.radius(), planet.rotation, ring_shader)
+        }
+    }
+
+    // Esfera algo mayor que la atmósfera alrededor de `planet`; solo sirve para generar
+    // fragmentos, la forma real de la capa la calcula el shader con esferas analíticas
+    pub fn atmosphere_shell(planet: &Body, atmosphere: Atmosphere) -> Self {
+        let scale = atmosphere.atmosphere_radius * 1.05 / SPHERE_MESH_RADIUS;
+        Body {
+            casts_shadow: false,
+            blend: BlendMode::Additive,
+            atmosphere: Some(atmosphere),
+            ..Body::new(Mesh::Sphere, planet.translation, scale, planet.rotation, atmosphere_shader)
+        }
+    }
+
+    // Esfera alrededor de la estrella `star` sobre la que se dibujan la corona y las
+    // protuberancias; la forma la calcula el shader a partir del rayo de la cámara
+    pub fn corona(star: &Body) -> Self {
+        Body {
+            casts_shadow: false,
+            blend: BlendMode::Additive,
+            emission_shader: Some(corona_shader),
+            ..Body::new(Mesh::Sphere, star.translation, star.scale * CORONA_EXTENT, star.rotation, corona_shader)
+        }
+    }
+
+    // Radio del cuerpo en coordenadas de mundo (para los anillos, el radio exterior)
+    pub fn radius(&self) -> f32 {
+        match self.mesh {
+            Mesh::Sphere | Mesh::FineSphere => SPHERE_MESH_RADIUS * self.scale,
+            Mesh::Ring => RING_MESH_RADII.1 * self.scale,
+        }
+    }
+
+    // Radio de la esfera centrada en el origen de la escena que contiene todo el cuerpo
+    pub fn bounding_radius(&self) -> f32 {
+        self.translation.magnitude() + self.radius()
+    }
+}
+
+pub struct Scene {
+    pub sun_position: Vec3,
+    pub sun_radius: f32,
+    pub lighting: Lighting,
+    pub cloud_layer: Option<CloudLayer>,
+    pub bodies: Vec<Body>,
+}
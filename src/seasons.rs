@@ -0,0 +1,81 @@
+use nalgebra_glm::{Vec3, smoothstep};
+use std::f32::consts::TAU;
+
+// Lo que hace falta de la órbita de un planeta para saber en qué estación está. El año se
+// mide en unidades de tiempo de la animación y empieza en el equinoccio de primavera del norte
+#[derive(Debug, Clone, Copy)]
+pub struct Orbit {
+    pub axial_tilt: f32,
+    pub year_length: f32,
+}
+
+impl Orbit {
+    pub fn earth() -> Self {
+        Orbit { axial_tilt: 23.44_f32.to_radians(), year_length: 2000.0 }
+    }
+
+    // El año de Marte dura 1.88 años terrestres
+    pub fn mars() -> Self {
+        Orbit { axial_tilt: 25.19_f32.to_radians(), year_length: 3760.0 }
+    }
+
+    // Ángulo recorrido en la órbita desde el equinoccio de primavera del norte
+    pub fn orbital_longitude(&self, time: f32) -> f32 {
+        TAU * time / self.year_length
+    }
+
+    // Latitud en la que el Sol está en el cénit a mediodía (declinación solar): positiva
+    // durante el verano del hemisferio norte y como mucho igual a la inclinación del eje
+    pub fn subsolar_latitude(&self, time: f32) -> f32 {
+        (self.axial_tilt.sin() * self.orbital_longitude(time).sin()).asin()
+    }
+}
+
+// Casquetes polares para la estación actual. En invierno el borde del casquete avanza hacia
+// el ecuador y en verano retrocede hacia el polo. Latitudes en radianes
+#[derive(Debug, Clone, Copy)]
+pub struct PolarCaps {
+    // Latitud del borde en los equinoccios
+    pub edge_latitude: f32,
+    // Cuánto se mueve el borde por cada radián de latitud subsolar
+    pub seasonal_swing: f32,
+    pub subsolar_latitude: f32,
+    pub color: Vec3,
+}
+
+impl PolarCaps {
+    // Marte: hielo de agua con una capa de hielo de CO2 que en invierno llega hasta unos 55°
+    pub fn mars(orbit: &Orbit, time: f32) -> Self {
+        PolarCaps {
+            edge_latitude: 70.0_f32.to_radians(),
+            seasonal_swing: 0.6,
+            subsolar_latitude: orbit.subsolar_latitude(time),
+            color: Vec3::new(0.95, 0.92, 0.88),
+        }
+    }
+
+    // Tierra: hielo marino y nieve alrededor de los polos
+    pub fn earth(orbit: &Orbit, time: f32) -> Self {
+        PolarCaps {
+            edge_latitude: 66.0_f32.to_radians(),
+            seasonal_swing: 0.45,
+            subsolar_latitude: orbit.subsolar_latitude(time),
+            color: Vec3::new(0.93, 0.96, 1.0),
+        }
+    }
+
+    // Latitud del borde de los casquetes norte y sur (valores absolutos)
+    pub fn edges(&self) -> (f32, f32) {
+        let shift = self.seasonal_swing * self.subsolar_latitude;
+        (self.edge_latitude + shift, self.edge_latitude - shift)
+    }
+
+    // Fracción de hielo (0 a 1) en `latitude`. `roughness` (alrededor de ±1) deforma el borde
+    // para que no sea un paralelo perfecto
+    pub fn coverage(&self, latitude: f32, roughness: f32) -> f32 {
+        let (north, south) = self.edges();
+        let edge = if latitude >= 0.0 { north } else { south };
+        let distance = latitude.abs() + roughness * 0.06 - edge;
+        smoothstep(-0.015, 0.015, distance)
+    }
+}
@@ -1,23 +1,34 @@
-use nalgebra_glm::{Vec2,Vec3, Vec4, Mat3, dot, mat4_to_mat3,normalize};
+use nalgebra_glm::{Vec3, Vec4, Mat3, dot, mat4_to_mat3,normalize, smoothstep};
 use crate::vertex::Vertex;
 
 use crate::Uniforms;
 use crate::fragment::Fragment;
 use crate::color::Color;
-use std::f32::consts::PI;
+use crate::light::fresnel;
+use crate::craters::CraterSample;
+use crate::scene::{CORONA_EXTENT, SPHERE_MESH_RADIUS};
 use rand::Rng;
-use rand::SeedableRng;
-use rand::rngs::StdRng;
+use std::f32::consts::{PI, TAU};
 
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+    // Los cuerpos con relieve desplazan el vértice sobre la esfera y recalculan su normal
+    let (model_position, model_normal) = match &uniforms.terrain {
+        Some(terrain) => {
+            let (position, normal, _) = terrain.displace(uniforms.terrain_noise, &vertex.position);
+            (position, normal)
+        }
+        None => (vertex.position, vertex.normal),
+    };
+
     // Transformar la posición del vértice
     let position = Vec4::new(
-        vertex.position.x,
-        vertex.position.y,
-        vertex.position.z,
+        model_position.x,
+        model_position.y,
+        model_position.z,
         1.0
     );
-    let transformed = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * position;
+    let world_position = uniforms.model_matrix * position;
+    let transformed = uniforms.projection_matrix * uniforms.view_matrix * world_position;
 
     // División perspectiva
     let w = transformed.w;
@@ -34,64 +45,240 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     // Transformar normales
     let model_mat3 = mat4_to_mat3(&uniforms.model_matrix); 
     let normal_matrix = model_mat3.transpose().try_inverse().unwrap_or(Mat3::identity());
-    let transformed_normal = normal_matrix * vertex.normal;
+    let transformed_normal = normal_matrix * model_normal;
 
     // Crear un nuevo vértice con atributos transformados
     Vertex {
-        position: vertex.position,
-        normal: vertex.normal,
+        position: model_position,
+        normal: model_normal,
         tex_coords: vertex.tex_coords,
+        tangent: vertex.tangent,
         color: vertex.color,
         transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
         transformed_normal,
+        world_position: world_position.xyz(),
+        view_direction: world_position.xyz() - uniforms.camera_position,
+    }
+}
+
+// El fragmento con la normal del relieve calculada por píxel, así los cráteres más pequeños
+// que los triángulos de la malla también se notan en la iluminación. También devuelve los
+// cráteres bajo el fragmento: forma del relieve para oscurecer fondos y aclarar bordes, y
+// albedo del material expulsado por los impactos jóvenes
+pub fn terrain_fragment(fragment: &Fragment, uniforms: &Uniforms) -> (Fragment, CraterSample) {
+    let Some(terrain) = &uniforms.terrain else {
+        return (*fragment, CraterSample::default());
+    };
+    let (_, normal, craters) = terrain.displace(uniforms.terrain_noise, &fragment.vertex_position);
+    let world_normal = normalize(&(mat4_to_mat3(&uniforms.model_matrix) * normal));
+    (Fragment { world_normal, ..*fragment }, craters)
+}
+
+// Brillo de borde (Fresnel) teñido con `tint`, más fuerte del lado iluminado por el Sol.
+// Es una alternativa barata a la capa de atmósfera para sugerir una neblina fina
+pub fn rim_light(fragment: &Fragment, uniforms: &Uniforms, tint: Vec3, strength: f32) -> Vec3 {
+    let point = fragment.world_position;
+    let normal = normalize(&fragment.world_normal);
+    let rim = fresnel(&normal, &-fragment.view_dir, 0.0, 3.0);
+    let sun_side = dot(&normal, &uniforms.lighting.sun_direction(&point)) * 0.5 + 0.5;
+    tint.component_mul(&uniforms.lighting.sun_radiance()) * (rim * sun_side * strength)
+}
+
+// Fracción de luz solar que llega al fragmento (1 = iluminado): mapa de sombras si está
+// activo, si no los eclipses analíticos de la escena
+pub fn sun_visibility(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    let world_position = fragment.world_position;
+    match &uniforms.shadow_map {
+        // Los anillos no están en el mapa: su sombra viene de los eclipses
+        Some(shadow_map) => {
+            shadow_map.visibility(&world_position, &normalize(&fragment.world_normal)) * uniforms.eclipses.visibility(&world_position)
+        }
+        None => uniforms.eclipses.visibility(&world_position),
+    }
+}
+
+// Para superficies delgadas que se ven por ambos lados (anillos) la normal se orienta hacia el Sol
+pub fn sun_visibility_two_sided(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    let world_position = fragment.world_position;
+    match &uniforms.shadow_map {
+        Some(shadow_map) => {
+            let normal = normalize(&fragment.world_normal);
+            let normal = if dot(&normal, &shadow_map.light_direction()) < 0.0 { -normal } else { normal };
+            shadow_map.visibility(&world_position, &normal) * uniforms.eclipses.visibility(&world_position)
+        }
+        None => uniforms.eclipses.visibility(&world_position),
+    }
+}
+
+// Luz ambiental más la difusa de la escena que llega al fragmento, con la sombra del Sol aplicada
+pub fn surface_light(fragment: &Fragment, uniforms: &Uniforms) -> Vec3 {
+    let point = fragment.world_position;
+    let normal = normalize(&fragment.world_normal);
+    let shadow = sun_visibility(fragment, uniforms);
+    uniforms.lighting.ambient() + uniforms.lighting.diffuse(&point, &normal, shadow)
+}
+
+// Capa de atmósfera: integra la dispersión de Rayleigh y Mie a lo largo del rayo de la cámara.
+// Se dibuja con mezcla aditiva sobre la superficie del planeta
+pub fn atmosphere_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let Some(atmosphere) = &uniforms.atmosphere else {
+        return Color::black();
+    };
+    let point = fragment.world_position;
+    let center = (uniforms.model_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz();
+    let direction = fragment.view_dir;
+    // Solo la cara frontal de la capa: el rayo ya recorre toda la atmósfera que atraviesa
+    if dot(&(point - center), &direction) > 0.0 {
+        return Color::black();
     }
+
+    let sun_direction = uniforms.lighting.sun_direction(&center);
+    let light = atmosphere
+        .scatter(&center, &uniforms.camera_position, &direction, &sun_direction)
+        .component_mul(&uniforms.lighting.sun_radiance());
+    // Exposición simple para llevar la luz acumulada a [0, 1]
+    Color::from_float(1.0 - (-light.x).exp(), 1.0 - (-light.y).exp(), 1.0 - (-light.z).exp())
+}
+
+// Manchas solares: cada grupo ocupa un hueco que se renueva cada SUNSPOT_LIFETIME unidades
+// de tiempo; el grupo crece, llega a su máximo y se disuelve. Los huecos están desfasados
+// para que siempre haya grupos en distintas fases
+const SUNSPOT_GROUPS: u32 = 6;
+const SUNSPOT_LIFETIME: f32 = 600.0;
+
+// Número pseudoaleatorio en [0, 1) a partir de un entero, igual en todos los fragmentos
+fn hash(value: u32) -> f32 {
+  let mut x = value.wrapping_mul(0x9E37_79B9) ^ 0x85EB_CA6B;
+  x ^= x >> 16;
+  x = x.wrapping_mul(0x7FEB_352D);
+  x ^= x >> 15;
+  x = x.wrapping_mul(0x846C_A68B);
+  x ^= x >> 16;
+  (x >> 8) as f32 / (1 << 24) as f32
 }
 
-pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    // Shader base (puede ser modificado según el planeta actual)
-    //time_based_color_cycling_shader(fragment, uniforms)
-    sun_shader(fragment, uniforms)
+// Oscurecimiento por manchas en la dirección `direction`: 0 fuera de las manchas, alrededor
+// de 0.5 en la penumbra y 1 en la umbra
+fn sunspots(direction: &Vec3, time: f32) -> f32 {
+  let latitude = direction.y.clamp(-1.0, 1.0).asin();
+  let longitude = direction.z.atan2(direction.x);
+  let mut darkness: f32 = 0.0;
+  for group in 0..SUNSPOT_GROUPS {
+    let cycle = time / SUNSPOT_LIFETIME + group as f32 / SUNSPOT_GROUPS as f32;
+    let seed = group.wrapping_mul(7919).wrapping_add((cycle.floor() as u32).wrapping_mul(104_729));
+    let strength = (cycle.fract() * PI).sin();
+
+    // Los grupos aparecen en las latitudes activas (entre unos 8° y 30°) y giran con la
+    // rotación diferencial: más rápido en el ecuador que cerca de los polos
+    let hemisphere = if hash(seed) < 0.5 { -1.0 } else { 1.0 };
+    let group_latitude = hemisphere * (0.15 + 0.35 * hash(seed.wrapping_add(1)));
+    let rotation = time * 0.002 * (1.0 - 0.2 * group_latitude.sin().powi(2));
+    let group_longitude = hash(seed.wrapping_add(2)) * 2.0 * PI + rotation;
+    let size = 0.09 * strength * (0.6 + 0.4 * hash(seed.wrapping_add(3)));
+
+    // Mancha principal delante, la seguidora detrás y una pequeña entre ambas
+    for (offset, relative_size) in [(0.0, 1.0), (0.07, 0.45), (0.13, 0.75)] {
+      let spot_latitude = group_latitude + hemisphere * offset * 0.15;
+      let spot_longitude = group_longitude - offset;
+      let delta_longitude = (longitude - spot_longitude + PI).rem_euclid(2.0 * PI) - PI;
+      let east = delta_longitude * latitude.cos();
+      let north = latitude - spot_latitude;
+      let distance = (east * east + north * north).sqrt() / (size * relative_size).max(1e-4);
+      // Filamentos radiales en la penumbra
+      let filaments = 0.85 + 0.15 * (north.atan2(east) * 30.0).sin();
+      let umbra = 1.0 - smoothstep(0.35, 0.45, distance);
+      let penumbra = (1.0 - smoothstep(0.8, 1.0, distance)) * 0.5 * filaments;
+      darkness = darkness.max(umbra.max(penumbra));
+    }
+  }
+  darkness
 }
 
+// Fotosfera: granulación de celdas de Voronoi (centros calientes y bordes oscuros donde el
+// gas se hunde), manchas solares y oscurecimiento hacia el borde del disco. Devuelve el
+// color y el brillo relativo, que usa la componente emisiva
+fn sun_surface(fragment: &Fragment, uniforms: &Uniforms) -> (Color, f32) {
+  let direction = normalize(&fragment.vertex_position);
+  let time = uniforms.time as f32;
+
+  // Las celdas cambian despacio al desplazar el corte del ruido 3D
+  let granule_point = direction * 40.0 + Vec3::new(1.0, 1.0, 1.0) * (time * 0.005);
+  let cell = uniforms.noise.get_noise_3d(granule_point.x, granule_point.y, granule_point.z);
+  let granulation = smoothstep(-1.0, -0.8, cell);
+  let hot = Vec3::new(1.0, 0.92, 0.6);
+  let lanes = Vec3::new(0.9, 0.68, 0.32);
+  let mut color = lanes.lerp(&hot, granulation);
+
+  let spots = sunspots(&direction, time);
+  let penumbra = Vec3::new(0.62, 0.32, 0.08);
+  let umbra = Vec3::new(0.22, 0.07, 0.02);
+  color = color.lerp(&penumbra, (spots * 2.0).min(1.0));
+  color = color.lerp(&umbra, ((spots - 0.5) * 2.0).max(0.0));
+
+  // Oscurecimiento del limbo: cerca del borde se ven capas más altas y frías. Con el
+  // coeficiente mayor en el azul el borde se vuelve más rojizo
+  let normal = normalize(&fragment.world_normal);
+  let mu = dot(&normal, &-fragment.view_dir).clamp(0.0, 1.0);
+  let limb_coefficients = Vec3::new(0.45, 0.6, 0.8);
+  let limb = Vec3::new(1.0, 1.0, 1.0) - limb_coefficients * (1.0 - mu);
+  color = color.component_mul(&limb);
+
+  let brightness = (color.x + color.y + color.z) / 3.0;
+  (Color::from_float(color.x, color.y, color.z), brightness)
+}
+
+// El Sol emite su propia luz, así que su color no depende de la iluminación
 pub fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  // Base colors for the sun effect
-  let bright_color = Color::new(255, 240, 0); // Bright orange (lava-like)
-  let dark_color = Color::new(130, 20, 0);   // Darker red-orange
-
-  // Get fragment position
-  let position = Vec3::new(
-    fragment.vertex_position.x,
-    fragment.vertex_position.y,
-    fragment.depth
-  );
-
-  // Base frequency and amplitude for the pulsating effect
-  let base_frequency = 0.2;
-  let pulsate_amplitude = 0.5;
-  let t = uniforms.time as f32 * 0.30;
-
-  // Pulsate on the z-axis to change spot size
-  let pulsate = (t * base_frequency).sin() * pulsate_amplitude;
-
-  // Apply noise to coordinates with subtle pulsating on z-axis
-  let zoom = 1000.0; // Constant zoom factor
-  let noise_value1 = uniforms.noise.get_noise_3d(
-    position.x * zoom,
-    position.y * zoom,
-    (position.z + pulsate) * zoom
-  );
-  let noise_value2 = uniforms.noise.get_noise_3d(
-    (position.x + 1000.0) * zoom,
-    (position.y + 1000.0) * zoom,
-    (position.z + 1000.0 + pulsate) * zoom
-  );
-  let noise_value = (noise_value1 + noise_value2) * 0.5;  // Averaging noise for smoother transitions
-
-  // Use lerp for color blending based on noise value
-  let color = dark_color.lerp(&bright_color, noise_value);
-
-  color * fragment.intensity
+  sun_surface(fragment, uniforms).0
+}
+
+// Componente emisiva para el bloom: brilla todo el disco y más las zonas calientes
+pub fn sun_emission_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+  let (color, brightness) = sun_surface(fragment, uniforms);
+  color * (0.1 + 0.3 * brightness)
 }
+
+// Corona y protuberancias alrededor del disco del Sol, en una esfera CORONA_EXTENT veces
+// mayor que la estrella. Para cada rayo de la cámara se mira a qué altura pasa del centro:
+// la corona cae con la altura y se rompe en serpentinas, y las protuberancias son llamas
+// rojizas pegadas al borde. Se dibuja con mezcla aditiva y también sirve de emisión
+pub fn corona_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+  let center = (uniforms.model_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz();
+  let direction = fragment.view_dir;
+  if dot(&(fragment.world_position - center), &direction) > 0.0 {
+    return Color::black();
+  }
+  let shell_radius = (uniforms.model_matrix * Vec4::new(SPHERE_MESH_RADIUS, 0.0, 0.0, 0.0)).xyz().magnitude();
+  let star_radius = shell_radius / CORONA_EXTENT;
+
+  // Punto del rayo más cercano al centro; si el rayo choca con el disco no hay corona
+  let to_center = center - uniforms.camera_position;
+  let closest = uniforms.camera_position + direction * dot(&to_center, &direction) - center;
+  let impact = closest.magnitude() / star_radius;
+  if impact < 1.0 {
+    return Color::black();
+  }
+  let height = impact - 1.0;
+  let radial = closest / closest.magnitude();
+  let time = uniforms.time as f32;
+
+  // Serpentinas: el brillo varía con el ángulo alrededor del disco y no con la altura
+  let streamer_point = radial * 3.0;
+  let streamers = uniforms.noise.get_noise_3d(streamer_point.x, streamer_point.y, streamer_point.z) * 0.5 + 0.5;
+  let corona = impact.powi(-5) * (0.35 + 0.65 * streamers) * (1.0 - smoothstep(0.6, 1.0, height / (CORONA_EXTENT - 1.0)));
+  let corona_color = Vec3::new(1.0, 0.95, 0.85) * corona * 0.8;
+
+  // Protuberancias: ruido de celdas estirado en altura que sube despacio
+  let flame_point = radial * 5.0 + Vec3::new(0.0, 0.0, height * 12.0 - time * 0.01);
+  let flames = uniforms.noise.get_noise_3d(flame_point.x, flame_point.y, flame_point.z);
+  let reach = 0.05 + 0.12 * streamers;
+  let prominence = smoothstep(-0.7, -0.4, flames) * (1.0 - smoothstep(0.0, reach, height));
+  let prominence_color = Vec3::new(1.0, 0.3, 0.25) * prominence * 0.9;
+
+  let light = corona_color + prominence_color;
+  Color::from_float(light.x, light.y, light.z)
+}
+
 pub fn time_based_color_cycling_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // Define una lista de colores para cambiar
     let colors = [
@@ -109,7 +296,22 @@ pub fn time_based_color_cycling_shader(fragment: &Fragment, uniforms: &Uniforms)
 
     let current_color = colors[color_index];
     let next_color = colors[(color_index + 1) % colors.len()];
-    current_color.lerp(&next_color, transition_progress) * fragment.intensity
+    current_color.lerp(&next_color, transition_progress) * surface_light(fragment, uniforms)
+}
+
+// Fracción de hielo de los casquetes polares en el fragmento. La latitud sale de la posición
+// sobre la esfera del modelo, así los casquetes giran con el planeta. El borde se deforma con
+// el ruido del planeta; `zoom` lleva la dirección (de longitud 1) a su escala y, multiplicado
+// por la frecuencia del ruido, debe dar unas pocas ondulaciones por radián
+fn polar_ice(fragment: &Fragment, uniforms: &Uniforms, zoom: f32) -> f32 {
+    let Some(caps) = &uniforms.polar_caps else {
+        return 0.0;
+    };
+    let direction = normalize(&fragment.vertex_position);
+    let latitude = direction.y.clamp(-1.0, 1.0).asin();
+    let position = direction * zoom;
+    let roughness = uniforms.noise.get_noise_3d(position.x, position.y, position.z);
+    caps.coverage(latitude, roughness)
 }
 
 pub fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
@@ -118,12 +320,8 @@ pub fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let mid_color = Color::new(160, 80, 30);      // Color medio, como marrón
     let dark_color = Color::new(100, 40, 20);     // Color oscuro para áreas sombreadas y cráteres
 
-    // Obtenemos la posición del fragmento
-    let position = Vec3::new(
-        fragment.vertex_position.x,
-        fragment.vertex_position.y,
-        fragment.depth,
-    );
+    // Obtenemos la posición del fragmento sobre la superficie (coordenadas del modelo)
+    let position = fragment.vertex_position;
 
     // Factor de zoom para mayor detalle en la textura
     let zoom = 1200.0;
@@ -133,10 +331,10 @@ pub fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let noise_value2 = uniforms.noise.get_noise_3d((position.x + 400.0) * zoom, (position.y + 400.0) * zoom, (position.z + 400.0) * zoom);
     let noise_value = (noise_value1 + noise_value2) * 0.5;
 
-    // Añadimos ruido para los cráteres en la superficie
-    let crater_frequency = 3.0;  // Aumentamos la frecuencia para más cráteres pequeños
-    let crater_amplitude = 0.6;  // Aumentamos la amplitud para hacerlos más evidentes
-    let crater_value = (position.x * crater_frequency).sin() * (position.y * crater_frequency).cos() * crater_amplitude;
+    // Los cráteres del relieve oscurecen su fondo y aclaran el borde
+    let crater_amplitude = 0.6;
+    let (surface, craters) = terrain_fragment(fragment, uniforms);
+    let crater_value = craters.relief * crater_amplitude;
 
     // Combinamos el ruido de la superficie y el ruido de los cráteres
     let mut combined_value = (noise_value + crater_value).clamp(0.0, 1.0);
@@ -150,12 +348,20 @@ pub fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     combined_value = (combined_value + fracture_noise).clamp(0.0, 1.0);
 
     // Determinamos el color de la superficie según el valor combinado
-    let base_color = if combined_value > 0.5 {
+    let mut base_color = if combined_value > 0.5 {
         mid_color.lerp(&bright_color, (combined_value - 0.5) * 1.5) // Mezclamos con el color brillante si el valor es alto
     } else {
         dark_color.lerp(&mid_color, combined_value * 2.0) // Mezclamos con el color oscuro si el valor es bajo
     };
 
+    // Casquetes polares de hielo, con el polvo de la superficie asomando en el borde
+    // El ruido de Marte tiene frecuencia 0.01: 400 × 0.01 = 4 ondulaciones por radián
+    let ice = polar_ice(fragment, uniforms, 400.0);
+    if let Some(caps) = &uniforms.polar_caps {
+        let ice_color = Color::from_float(caps.color.x, caps.color.y, caps.color.z) * (0.85 + 0.15 * combined_value);
+        base_color = base_color.lerp(&ice_color, ice);
+    }
+
     // Iluminación difusa (suavizada) para simular la luz sobre la superficie
     let light_factor = (position.y * 0.5 + uniforms.time as f32 * 0.0015).sin() * 0.1 + 1.0;
     let directional_light = (position.x * 0.3 + uniforms.time as f32 * 0.002).cos() * 0.05 + 1.0;
@@ -174,8 +380,11 @@ pub fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let shadow_texture_noise = uniforms.noise.get_noise_3d(position.x * 3500.0, position.y * 3500.0, position.z * 3500.0) * 0.4;
     final_color = final_color * (1.0 - shadow_texture_noise);
 
-    // Devolvemos el color final multiplicado por la intensidad del fragmento
-    final_color * fragment.intensity
+    // Neblina de polvo en el borde del disco
+    let haze = rim_light(fragment, uniforms, Vec3::new(0.9, 0.55, 0.35), 0.4);
+
+    // Devolvemos el color final con la luz de la escena sobre el relieve
+    final_color * surface_light(&surface, uniforms) + Color::from_float(haze.x, haze.y, haze.z)
 }
 
 
@@ -185,64 +394,165 @@ pub fn mars_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color {
 
 
 
-pub fn earth_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let time = uniforms.time as f32; // Usamos el tiempo dinámico que viene de los uniforms
+// Valor del ruido de la Tierra a partir del cual hay tierra firme en lugar de océano
+const EARTH_LAND_THRESHOLD: f32 = 0.3;
+// Escala del ruido en el borde de los casquetes: con la frecuencia 0.5 del ruido de la Tierra
+// quedan 4 ondulaciones por radián
+const EARTH_ICE_ZOOM: f32 = 8.0;
 
-    // Variables para las coordenadas 2D (posición) del fragmento
-    let x = fragment.vertex_position.x;
-    let y = fragment.vertex_position.y;
-    
-    // Animación de nubes basada en el tiempo
-    let moving_x = x + time * 0.2;  // Velocidad de movimiento en X
-    let moving_y = y + time * 0.1;  // Velocidad de movimiento en Y
+fn earth_land_noise(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    uniforms.noise.get_noise_2d(fragment.vertex_position.x, fragment.vertex_position.y)
+}
 
-    // Valores de ruido para la textura de la superficie y para las nubes
-    let base_noise_value = uniforms.noise.get_noise_2d(x, y);
-    let cloud_noise_value = uniforms.cloud_noise.get_noise_2d(moving_x * 100.0, moving_y * 100.0); // Desplazamiento de nubes
+// Luces de las ciudades: solo sobre tierra firme y del lado nocturno, se apagan durante el crepúsculo
+fn earth_city_lights(fragment: &Fragment, uniforms: &Uniforms, land_noise: f32) -> Color {
+    if land_noise <= EARTH_LAND_THRESHOLD {
+        return Color::black();
+    }
+    // Bajo el hielo no hay ciudades
+    let ice = polar_ice(fragment, uniforms, EARTH_ICE_ZOOM);
+    if ice >= 1.0 {
+        return Color::black();
+    }
+    let normal = normalize(&fragment.world_normal);
+    let sun_cos = dot(&normal, &uniforms.lighting.sun_direction(&fragment.world_position));
+    let night = 1.0 - smoothstep(-0.2, 0.05, sun_cos);
+    if night <= 0.0 {
+        return Color::black();
+    }
 
-    // Colores base para el agua, tierra y nubes
+    // Ruido de alta frecuencia para agrupar las luces en ciudades
+    let position = fragment.vertex_position * 150.0;
+    let cities = uniforms.noise.get_noise_3d(position.x, position.y, position.z);
+    let density = smoothstep(0.6, 0.9, cities);
+    Color::from_float(1.0, 0.78, 0.45) * (0.8 * density * night * (1.0 - ice))
+}
+
+pub fn earth_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let land_noise = earth_land_noise(fragment, uniforms);
+
+    // Colores base para el agua y la tierra
     let water_color_1 = Color::from_float(0.0, 0.1, 0.6); // Azul oscuro
     let water_color_2 = Color::from_float(0.0, 0.3, 0.7); // Azul claro
     let land_color_1 = Color::from_float(0.1, 0.5, 0.0); // Verde oscuro
     let land_color_2 = Color::from_float(0.2, 0.8, 0.2); // Verde claro
-    let cloud_color = Color::from_float(0.9, 0.9, 0.9); // Blanco para las nubes
-
-    let land_threshold = 0.3; // Umbral para determinar si es agua o tierra
 
     // Determinar el color base del fragmento entre agua y tierra
-    let base_color = if base_noise_value > land_threshold {
+    let is_land = land_noise > EARTH_LAND_THRESHOLD;
+    let mut base_color = if is_land {
         // Tierra
-        land_color_1.lerp(&land_color_2, (base_noise_value - land_threshold) / (1.0 - land_threshold))
+        land_color_1.lerp(&land_color_2, (land_noise - EARTH_LAND_THRESHOLD) / (1.0 - EARTH_LAND_THRESHOLD))
     } else {
         // Agua
-        water_color_1.lerp(&water_color_2, base_noise_value / land_threshold)
+        water_color_1.lerp(&water_color_2, land_noise / EARTH_LAND_THRESHOLD)
     };
 
-    // Iluminación difusa (suave) para resaltar la superficie
-    let light_position = Vec3::new(1.0, 1.0, 3.0); // Dirección de la luz (sol)
-    let light_dir = normalize(&(light_position - fragment.vertex_position)); // Dirección de la luz
-    let normal = normalize(&fragment.normal); // Normal del fragmento
-    let diffuse = dot(&normal, &light_dir).max(0.0); // Cálculo de la iluminación difusa
+    // Nieve y hielo marino alrededor de los polos, según la estación
+    let ice = polar_ice(fragment, uniforms, EARTH_ICE_ZOOM);
+    if let Some(caps) = &uniforms.polar_caps {
+        base_color = base_color.lerp(&Color::from_float(caps.color.x, caps.color.y, caps.color.z), ice);
+    }
+
+    let point = fragment.world_position;
+    let normal = normalize(&fragment.world_normal);
+    let sun_direction = uniforms.lighting.sun_direction(&point);
+    let sun_cos = dot(&normal, &sun_direction);
+    // Sombra de la luna y de las nubes que hay entre el punto y el Sol
+    let cloud_shadow = uniforms
+        .cloud_layer
+        .map_or(0.0, |layer| layer.shadow(&uniforms.cloud_noise, &point, &sun_direction));
+    let shadow = sun_visibility(fragment, uniforms) * (1.0 - 0.6 * cloud_shadow);
+
+    // El terminador sale de la luz difusa del Sol
+    let light = uniforms.lighting.ambient() + uniforms.lighting.diffuse(&point, &normal, shadow);
+    let lit_color = base_color * light;
+
+    // Banda de crepúsculo: tono rojizo donde el Sol está sobre el horizonte
+    let twilight = (-(sun_cos / 0.15).powi(2)).exp() * shadow;
+    let twilight_color = Color::from_float(1.0, 0.45, 0.2) * (0.35 * twilight);
+
+    // Reflejo especular del Sol solo sobre el océano
+    let glint = if is_land {
+        Vec3::zeros()
+    } else {
+        uniforms.lighting.specular(&point, &normal, &-fragment.view_dir, 60.0, shadow) * (0.8 * (1.0 - ice))
+    };
 
-    // Aplicar el color base con iluminación difusa
-    let lit_color = base_color * (0.1 + 0.9 * diffuse); // Agregar un factor de luz
+    lit_color
+        + twilight_color
+        + Color::from_float(glint.x, glint.y, glint.z)
+        + earth_city_lights(fragment, uniforms, land_noise)
+}
 
-    // Umbral para las nubes
-    let cloud_threshold = 0.1;
-    
-    let cloud_opacity = 0.8 + 0.2 * ((time / 1000.0) * 0.5).sin().abs(); // Opacidad alta
+// Componente emisiva para el bloom: las luces de las ciudades
+pub fn earth_emission_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    earth_city_lights(fragment, uniforms, earth_land_noise(fragment, uniforms))
+}
 
-    // Comprobar si debemos dibujar nubes en este fragmento
-    if cloud_noise_value > cloud_threshold {
-        let cloud_intensity = ((cloud_noise_value - cloud_threshold) / (1.0 - cloud_threshold)).clamp(0.0, 1.0);
-        // Mezclar el color base con las nubes
-        return lit_color.blend_add(&(cloud_color * (cloud_intensity * cloud_opacity)));
-    } else {
-        // No hay nubes, simplemente retornar el color lit
-        return lit_color;
+// Nubes de la capa transparente, iluminadas como cualquier superficie
+pub fn cloud_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    Color::from_float(0.95, 0.95, 0.95) * surface_light(fragment, uniforms)
+}
+
+// Opacidad de la capa de nubes; la cara trasera de la esfera no se dibuja
+pub fn cloud_alpha_shader(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    let Some(layer) = &uniforms.cloud_layer else {
+        return 0.0;
+    };
+    if dot(&(fragment.world_position - layer.center), &fragment.view_dir) > 0.0 {
+        return 0.0;
     }
+    layer.coverage(&uniforms.cloud_noise, &fragment.vertex_position)
+}
+
+
+// Tormenta de Júpiter: un óvalo anclado a una latitud que deriva en longitud. Dentro del óvalo
+// el viento gira y arrastra las bandas de alrededor. Ángulos en radianes
+struct Vortex {
+    latitude: f32,
+    longitude: f32,
+    // Deriva en longitud por unidad de tiempo
+    drift: f32,
+    // Semiejes del óvalo hacia el este y hacia el norte
+    east_radius: f32,
+    north_radius: f32,
+    // Giro máximo que se aplica a las bandas en el centro (positivo = antihorario visto desde fuera)
+    swirl: f32,
+    color: Vec3,
 }
 
+// La Gran Mancha Roja, el óvalo BA (más pequeño y rojizo), óvalos blancos del sur y
+// las barcazas marrones de la banda ecuatorial norte
+const JUPITER_VORTICES: [Vortex; 6] = [
+    Vortex { latitude: -0.38, longitude: 1.7, drift: 0.0015, east_radius: 0.3, north_radius: 0.13, swirl: 2.4, color: Vec3::new(0.78, 0.36, 0.2) },
+    Vortex { latitude: -0.58, longitude: 1.0, drift: 0.001, east_radius: 0.1, north_radius: 0.06, swirl: 1.8, color: Vec3::new(0.85, 0.6, 0.48) },
+    Vortex { latitude: -0.72, longitude: 1.4, drift: 0.0008, east_radius: 0.06, north_radius: 0.04, swirl: 1.5, color: Vec3::new(0.97, 0.94, 0.88) },
+    Vortex { latitude: -0.72, longitude: 2.1, drift: 0.0008, east_radius: 0.05, north_radius: 0.035, swirl: 1.5, color: Vec3::new(0.97, 0.94, 0.88) },
+    Vortex { latitude: 0.28, longitude: 1.3, drift: -0.001, east_radius: 0.1, north_radius: 0.035, swirl: -1.2, color: Vec3::new(0.5, 0.3, 0.2) },
+    Vortex { latitude: 0.28, longitude: 2.3, drift: -0.001, east_radius: 0.08, north_radius: 0.03, swirl: -1.2, color: Vec3::new(0.5, 0.3, 0.2) },
+];
+
+impl Vortex {
+    // Posición del óvalo respecto al punto (latitud, longitud), en unidades de sus semiejes
+    fn local_offset(&self, latitude: f32, longitude: f32, time: f32) -> (f32, f32) {
+        let center_longitude = self.longitude + self.drift * time;
+        let delta_longitude = (longitude - center_longitude + PI).rem_euclid(2.0 * PI) - PI;
+        (delta_longitude * latitude.cos() / self.east_radius, (latitude - self.latitude) / self.north_radius)
+    }
+
+    // Deformación del dominio: gira el punto alrededor del centro con un ángulo que cae con
+    // la distancia, así las bandas cercanas se enroscan alrededor de la tormenta
+    fn warp(&self, latitude: f32, longitude: f32, time: f32) -> (f32, f32) {
+        let (east, north) = self.local_offset(latitude, longitude, time);
+        let distance_squared = east * east + north * north;
+        let (sin, cos) = (self.swirl * (-distance_squared * 0.8).exp()).sin_cos();
+        let warped_east = east * cos - north * sin;
+        let warped_north = east * sin + north * cos;
+        let warped_latitude = self.latitude + warped_north * self.north_radius;
+        let warped_longitude = longitude + (warped_east - east) * self.east_radius / latitude.cos().max(0.1);
+        (warped_latitude, warped_longitude)
+    }
+}
 
 pub fn jupiter_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let base_colors = [
@@ -253,16 +563,32 @@ pub fn jupiter_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color
         Vec3::new(0.95, 0.90, 0.75), // Blanco crema
     ];
 
-    let time = uniforms.time as f32 * 0.001;
-    let dynamic_y = fragment.vertex_position.y + time;
+    let time = uniforms.time as f32;
+    let position = fragment.vertex_position;
+    let radius = position.magnitude();
+    let direction = position / radius;
+    let mut latitude = direction.y.clamp(-1.0, 1.0).asin();
+    let mut longitude = direction.z.atan2(direction.x);
+
+    // Las bandas se leen en coordenadas deformadas por las tormentas
+    for vortex in &JUPITER_VORTICES {
+        (latitude, longitude) = vortex.warp(latitude, longitude, time);
+    }
 
-    let distortion_scale = 10.0;
-    let distortion_value = uniforms.noise.get_noise_2d(
-        fragment.vertex_position.x * distortion_scale,
-        dynamic_y * distortion_scale,
+    // Turbulencia arrastrada por los chorros zonales, que cambian de sentido de una banda a
+    // otra: la longitud avanza según la latitud y el ruido se estira a lo largo de las bandas
+    let zonal_wind = (latitude * 12.0).sin();
+    // El desplazamiento se reduce a una vuelta para que no crezca sin límite con el tiempo
+    let flow_longitude = longitude + (zonal_wind * time * 0.002).rem_euclid(TAU);
+    let (sin_flow, cos_flow) = flow_longitude.sin_cos();
+    let turbulence_scale = 120.0;
+    let turbulence = uniforms.noise.get_noise_3d(
+        cos_flow * latitude.cos() * turbulence_scale,
+        latitude.sin() * turbulence_scale * 4.0,
+        sin_flow * latitude.cos() * turbulence_scale,
     );
 
-    let distorted_y = dynamic_y + distortion_value * 0.1 + fragment.vertex_position.x * 0.05;
+    let distorted_y = latitude.sin() * radius + turbulence * 0.03;
 
     let band_frequency = 40.0;
     let band_sine = (distorted_y * band_frequency).sin();
@@ -311,43 +637,46 @@ pub fn jupiter_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color
         fragment.vertex_position.y * shadow_noise_scale,
     );
     let shadow_variation = 1.0 - shadow_noise * 0.05;
-    let final_shaded_color = shaded_color * shadow_variation;
-    let spot_noise_scale = 25.0;
-    let spot_noise = uniforms.noise.get_noise_2d(
-        fragment.vertex_position.x * spot_noise_scale,
-        fragment.vertex_position.y * spot_noise_scale,
-    );
-
-    let mut final_color;
-
-    if spot_noise > 0.75 {
-        let mix_factor = (spot_noise - 0.75) / 0.25;
-        let storm_color = Vec3::new(0.95, 0.85, 0.65);
-        final_color = final_shaded_color.lerp(&storm_color, mix_factor);
-    } else {
-        final_color = final_shaded_color;
+    let mut final_color = shaded_color * shadow_variation;
+
+    // Color propio de cada tormenta, con nubes internas que giran con ella
+    let true_latitude = direction.y.clamp(-1.0, 1.0).asin();
+    let true_longitude = direction.z.atan2(direction.x);
+    for (index, vortex) in JUPITER_VORTICES.iter().enumerate() {
+        let (east, north) = vortex.local_offset(true_latitude, true_longitude, time);
+        let distance = (east * east + north * north).sqrt();
+        let inside = 1.0 - smoothstep(0.7, 1.0, distance);
+        if inside <= 0.0 {
+            continue;
+        }
+        let (sin, cos) = (vortex.swirl * (1.0 - distance) + time * 0.01 * vortex.swirl.signum()).sin_cos();
+        let offset = index as f32 * 100.0;
+        let clouds = uniforms.noise.get_noise_2d(
+            (east * cos - north * sin) * 60.0 + offset,
+            (east * sin + north * cos) * 60.0 + offset,
+        );
+        // Núcleo más intenso y un borde algo más claro
+        let core = vortex.color * (1.0 + clouds * 0.25) * (0.9 + 0.1 * distance);
+        final_color = final_color.lerp(&core, inside);
     }
 
-    let normal = fragment.vertex_position.normalize();
+    let point = fragment.world_position;
+    let normal = normalize(&fragment.world_normal);
+    let shadow = sun_visibility(fragment, uniforms);
 
-    let light_dir = Vec3::new(0.6, 0.8, 0.4).normalize();
-    let lambertian = light_dir.dot(&normal).max(0.0);
-    let shading_factor = 0.75 + 0.25 * lambertian;
-
-    final_color = final_color * shading_factor;
+    let light = uniforms.lighting.ambient() + uniforms.lighting.diffuse(&point, &normal, shadow);
+    final_color = final_color.component_mul(&light);
 
     // dispersión atmosférica
     let gradient_shading = 1.0 - (fragment.vertex_position.y.abs() * 0.15);
-    final_color = final_color * gradient_shading;
+    final_color *= gradient_shading;
 
     // reflejos especulares para simular brillos en la atmósfera
-    let view_dir = Vec3::new(0.0, 0.0, 1.0).normalize();
-    let reflect_dir = (2.0 * normal.dot(&light_dir) * normal - light_dir).normalize();
-    let specular_intensity = view_dir.dot(&reflect_dir).max(0.0).powf(10.0);
-
-    final_color = final_color + Vec3::new(1.0, 1.0, 1.0) * specular_intensity * 0.15;
+    let view_dir = -fragment.view_dir;
+    let specular = uniforms.lighting.specular(&point, &normal, &view_dir, 40.0, shadow);
 
-    final_color = final_color * fragment.intensity;
+    final_color += specular * 0.15;
+    final_color += rim_light(fragment, uniforms, Vec3::new(1.0, 0.85, 0.6), 0.25);
 
     Color::new(
         (final_color.x * 255.0) as u8,
@@ -358,57 +687,139 @@ pub fn jupiter_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color
 
 
 
-pub fn uranus_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (Color, u32) {
-    let zoom = 100.0;
-    let ox = 100.0;
-    let oy = 100.0;
-    let x = fragment.vertex_position.x;
-    let y = fragment.vertex_position.y;
-    let t = time as f32 * 0.1;
-
-    let base_noise_value = uniforms.noise.get_noise_2d(x, y);
-    let cloud_noise_value = uniforms.cloud_noise.get_noise_2d(x * zoom + ox + t, y * zoom + oy);
-
-    let water_color_1 = Color::from_float(0.0, 0.1, 0.6);
-    let water_color_2 = Color::from_float(0.0, 0.3, 0.7);
-    let land_color_1 = Color::from_float(0.1, 0.5, 0.0);
-    let land_color_2 = Color::from_float(0.2, 0.8, 0.2);
-    let cloud_color = Color::from_float(0.9, 0.9, 0.9);
+// Urano: neblina de metano cian casi uniforme, con bandas muy tenues y el polo algo más claro
+pub fn uranus_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let position = fragment.vertex_position;
+    // Seno de la latitud: las bandas y el casquete se miden sobre el eje Y, no en ángulo
+    let sin_latitude = normalize(&position).y;
+
+    let band_noise = uniforms.noise.get_noise_3d(position.x * 600.0, position.y * 600.0, position.z * 600.0);
+    let bands = ((sin_latitude + band_noise * 0.05) * 14.0).sin() * 0.5 + 0.5;
+    let deep = Vec3::new(0.55, 0.82, 0.88);
+    let pale = Vec3::new(0.7, 0.9, 0.93);
+    let polar_cap = smoothstep(0.6, 0.95, sin_latitude.abs()) * 0.12;
+    let color = deep.lerp(&pale, bands * 0.3) + Vec3::repeat(polar_cap);
+
+    let lit = color.component_mul(&surface_light(fragment, uniforms));
+    let haze = rim_light(fragment, uniforms, Vec3::new(0.6, 0.9, 1.0), 0.3);
+    let final_color = lit + haze;
+    Color::from_float(final_color.x, final_color.y, final_color.z)
+}
 
-    let land_threshold = 0.3;
+pub fn uranus_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    uranus_shader(fragment, uniforms)
+}
 
-    let base_color = if base_noise_value > land_threshold {
-        land_color_1.lerp(&land_color_2, (base_noise_value - land_threshold) / (1.0 - land_threshold))
-    } else {
-        water_color_1.lerp(&water_color_2, base_noise_value / land_threshold)
-    };
+// Neptuno: azul intenso con bandas visibles, la Gran Mancha Oscura con sus nubes
+// compañeras y cirros blancos estirados por los vientos
+pub fn neptune_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let position = fragment.vertex_position;
+    let direction = normalize(&position);
+    let latitude = direction.y.clamp(-1.0, 1.0).asin();
+    let longitude = direction.z.atan2(direction.x);
+    let time = uniforms.time as f32;
+
+    let band_noise = uniforms.noise.get_noise_3d(position.x * 600.0, position.y * 600.0, position.z * 600.0);
+    let bands = ((latitude * 7.0 + band_noise * 0.8).sin() * 0.5 + 0.5).powi(2);
+    let deep = Vec3::new(0.12, 0.27, 0.72);
+    let light = Vec3::new(0.28, 0.48, 0.9);
+    let mut color = deep.lerp(&light, bands * 0.6);
+
+    // La mancha oscura es un óvalo a unos 20° sur que deriva en longitud
+    let spot_latitude = -20.0_f32.to_radians();
+    let spot_longitude = 1.3 + time * 0.004;
+    let delta_longitude = (longitude - spot_longitude + PI).rem_euclid(2.0 * PI) - PI;
+    let east = delta_longitude * latitude.cos();
+    let spot_distance = ((east / 0.3).powi(2) + ((latitude - spot_latitude) / 0.14).powi(2)).sqrt();
+    let dark_spot = 1.0 - smoothstep(0.6, 1.0, spot_distance);
+    color = color.lerp(&Vec3::new(0.03, 0.06, 0.28), dark_spot * 0.9);
+
+    // Nubes brillantes que acompañan a la mancha por su borde sur
+    let companion_distance = (((east - 0.05) / 0.32).powi(2) + ((latitude - spot_latitude + 0.16) / 0.04).powi(2)).sqrt();
+    let companion = 1.0 - smoothstep(0.3, 1.0, companion_distance);
+
+    // Cirros: ruido mucho más fino en latitud que en longitud, girando con el viento
+    let (sin_drift, cos_drift) = (time * 0.002).sin_cos();
+    let drifted = Vec3::new(cos_drift * position.x - sin_drift * position.z, position.y, sin_drift * position.x + cos_drift * position.z);
+    let streaks = uniforms.noise.get_noise_3d(drifted.x * 400.0, drifted.y * 2500.0, drifted.z * 400.0);
+    let cirrus = smoothstep(0.2, 0.45, streaks) * 0.7;
+    color = color.lerp(&Vec3::new(0.92, 0.95, 1.0), (cirrus + companion * 0.9).min(1.0));
+
+    let lit = color.component_mul(&surface_light(fragment, uniforms));
+    let haze = rim_light(fragment, uniforms, Vec3::new(0.35, 0.55, 1.0), 0.3);
+    let final_color = lit + haze;
+    Color::from_float(final_color.x, final_color.y, final_color.z)
+}
 
-    let light_position = Vec3::new(1.0, 1.0, 3.0);
-    let light_dir = (light_position - fragment.vertex_position).normalize();
-    let normal = fragment.normal.normalize();
-    let diffuse = normal.dot(&light_dir).max(0.0);
-    if diffuse.is_nan() || diffuse.is_infinite() {
-        panic!("Diffuse calculation resulted in NaN or infinity!");
-    }
+pub fn neptune_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    neptune_shader(fragment, uniforms)
+}
 
-    let lit_color = base_color * (0.1 + 0.9 * diffuse);
+// Superficie de Venus: llanuras de basalto con coladas de lava, casi siempre oculta bajo las
+// nubes. Solo llega la luz que atraviesa los huecos de la capa
+pub fn venus_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let position = fragment.vertex_position;
+    let plains = uniforms.noise.get_noise_3d(position.x * 300.0, position.y * 300.0, position.z * 300.0);
+    let flows = smoothstep(0.1, 0.4, plains);
+    let basalt = Vec3::new(0.3, 0.2, 0.13);
+    let lava_flow = Vec3::new(0.45, 0.3, 0.18);
+    let color = basalt.lerp(&lava_flow, flows);
+
+    let point = fragment.world_position;
+    let normal = normalize(&fragment.world_normal);
+    let sun_direction = uniforms.lighting.sun_direction(&point);
+    let cloud_shadow = uniforms
+        .cloud_layer
+        .map_or(0.0, |layer| layer.shadow(&uniforms.cloud_noise, &point, &sun_direction));
+    let shadow = sun_visibility(fragment, uniforms) * (1.0 - 0.9 * cloud_shadow);
+    let light = uniforms.lighting.ambient() + uniforms.lighting.diffuse(&point, &normal, shadow);
+    let lit = color.component_mul(&light);
+    Color::from_float(lit.x, lit.y, lit.z)
+}
 
-    let cloud_threshold = 0.1;
-    let cloud_opacity = 0.3 + 0.2 * ((time as f32 / 1000.0) * 0.3).sin().abs();
-    if cloud_noise_value > cloud_threshold {
-        let cloud_intensity = ((cloud_noise_value - cloud_threshold) / (1.0 - cloud_threshold)).clamp(0.0, 1.0);
-        (lit_color.blend_add(&(cloud_color * (cloud_intensity * cloud_opacity))), 0)
-    } else {
-        (lit_color, 0)
-    }
+pub fn venus_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    venus_shader(fragment, uniforms)
 }
 
-pub fn uranus_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let (color, _extra) = uranus_shader(fragment, uniforms, 0); // Replace `0` with actual `time` if needed
-    color
+// Nubes de ácido sulfúrico de Venus, blanco amarillentas. Las marcas oscuras del ultravioleta
+// forman una Y tumbada: el tronco sobre el ecuador y dos brazos que se abren hacia latitudes
+// altas y se quedan atrás porque el viento gira más despacio lejos del ecuador. Las coordenadas
+// son las de la capa, así el dibujo gira con la superrotación
+pub fn venus_cloud_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let position = fragment.vertex_position;
+    let direction = normalize(&position);
+    let latitude = direction.y.clamp(-1.0, 1.0).asin();
+    let longitude = direction.z.atan2(direction.x);
+
+    // Bandas zonales: ruido mucho más fino en latitud que en longitud
+    let streaks = uniforms.noise.get_noise_3d(position.x * 300.0, position.y * 1500.0, position.z * 300.0);
+    let mottling = uniforms.noise.get_noise_3d(position.x * 900.0, position.y * 900.0, position.z * 900.0);
+
+    // Distancia en longitud al vértice de la Y, los brazos siguen latitud = ±0.45·d
+    let wobble = streaks * 0.08;
+    let behind = (longitude - 0.5 + PI).rem_euclid(2.0 * PI) - PI;
+    let arm_latitude = behind.max(0.0) * 0.45;
+    let arm_width = 0.14 + behind.max(0.0) * 0.05;
+    let arm_distance = (latitude.abs() - arm_latitude + wobble) / arm_width;
+    let along = smoothstep(-2.4, -1.6, behind) * (1.0 - smoothstep(1.8, 2.6, behind));
+    let y_feature = (-arm_distance * arm_distance).exp() * along;
+
+    // Bandas oscuras tenues en latitudes bajas y el collar brillante alrededor de los polos
+    let low_latitude_bands = (1.0 - smoothstep(0.5, 0.9, latitude.abs())) * smoothstep(0.05, 0.35, streaks) * 0.2;
+    let polar_collar = smoothstep(0.95, 1.15, latitude.abs());
+
+    let cloud = Vec3::new(0.96, 0.9, 0.7);
+    let ultraviolet_dark = Vec3::new(0.72, 0.58, 0.38);
+    let darkening = ((y_feature * 0.8 + low_latitude_bands) * (1.0 + mottling * 0.5)).clamp(0.0, 0.8);
+    let color = cloud.lerp(&ultraviolet_dark, darkening) + Vec3::new(0.04, 0.04, 0.03) * polar_collar;
+
+    let lit = color.component_mul(&surface_light(fragment, uniforms));
+    let haze = rim_light(fragment, uniforms, Vec3::new(1.0, 0.85, 0.55), 0.25);
+    let final_color = lit + haze;
+    Color::from_float(final_color.x, final_color.y, final_color.z)
 }
 
-pub fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (Color, u32) {
+pub fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms, _time: u32) -> (Color, u32) {
     let latitude = fragment.vertex_position.y;
     let band_frequency = 10.0;
 
@@ -436,60 +847,65 @@ pub fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms, time: u32) -> (Co
     let turbulence_intensity = 0.3;
     let turbulence_color = base_color.lerp(&Color::from_hex(0xffffff), turbulence_intensity);
 
-    let light_position = Vec3::new(0.0, 8.0, 9.0);
-    let light_direction = (light_position - fragment.vertex_position).normalize();
-    let normal = fragment.normal.normalize();
-    let diffuse = normal.dot(&light_direction).max(0.0);
-    if diffuse.is_nan() || diffuse.is_infinite() {
-        panic!("Diffuse calculation resulted in NaN or infinity!");
-    }
-
-    let ambient_intensity = 0.15;
-    let ambient_color = turbulence_color * ambient_intensity;
-    let lit_color = turbulence_color * diffuse;
-
-    (ambient_color + lit_color, 0)
+    // La luz del Sol incluye la sombra de los anillos
+    (turbulence_color * surface_light(fragment, uniforms), 0)
 }
 
 pub fn saturn_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let (color, _extra) = saturn_shader(fragment, uniforms, 0); 
     color
 }
-pub fn saturn_ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let ring_position = Vec2::new(fragment.vertex_position.x, fragment.vertex_position.z);
-    let distance_from_center = ring_position.magnitude(); // Distancia radial al centro
-
-    let num_bands = 8; // Más bandas para los anillos
-    let max_distance = 1.5; // Tamaño reducido
-    let band_width = max_distance / num_bands as f32;
-
-    let band_index = (distance_from_center / band_width).floor() as i32;
-
-    let band_colors = [
-        Color::new(225, 190, 160), // Marrón claro
-        Color::new(245, 230, 200), // Beige claro
-        Color::new(255, 255, 240), // Blanco crema
-        Color::new(200, 180, 150), // Marrón pálido
-        Color::new(230, 210, 190), // Beige intermedio
-    ];
-
-    let base_color = band_colors[(band_index.abs() as usize) % band_colors.len()];
-
-    let edge_distance = (distance_from_center % band_width) / band_width;
-    let smooth_edge = (1.0 - edge_distance).clamp(0.0, 1.0);
-
-    let light_position = Vec3::new(1.0, 1.0, 3.0);
-    let light_dir = normalize(&(light_position - fragment.vertex_position));
-    let normal = normalize(&fragment.normal);
-    let diffuse_intensity = dot(&normal, &light_dir).max(0.0);
-
-    let ambient_intensity = 0.3;
-    let final_light_factor = ambient_intensity + (1.0 - ambient_intensity) * diffuse_intensity;
+// Anillos a partir del perfil radial del cuerpo (uniforms.rings). El color es el de las
+// partículas iluminadas; la transparencia la pone ring_alpha_shader
+pub fn ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+    let Some(profile) = &uniforms.rings else {
+        return Color::black();
+    };
+    let radius = ring_radius(fragment, uniforms);
+    let depth = profile.optical_depth(radius, Some(&uniforms.noise));
+    let color = profile.color(radius);
+
+    let point = fragment.world_position;
+    let normal = normalize(&fragment.world_normal);
+    let sun_direction = uniforms.lighting.sun_direction(&point);
+    let to_camera = -fragment.view_dir;
+    let sun_cos = dot(&normal, &sun_direction);
+    let shadow = sun_visibility_two_sided(fragment, uniforms); // Sombra del planeta sobre el anillo
+
+    // Si el Sol y la cámara están del mismo lado se ve la cara iluminada; si no, solo llega
+    // la luz que atraviesa el anillo, mucha en las zonas tenues y casi nada en el anillo B
+    let view_cos = dot(&normal, &to_camera);
+    let lit_side = sun_cos * view_cos > 0.0;
+    let sun_slant = sun_cos.abs().max(0.02);
+    let view_slant = view_cos.abs().max(0.02);
+    let transmitted = if lit_side { 1.0 } else { (-depth / sun_slant).exp() };
+    // Las partículas de hielo dispersan sobre todo hacia delante: los anillos brillan a contraluz
+    let forward = dot(&sun_direction, &fragment.view_dir).max(0.0);
+    let phase = if lit_side { 1.0 } else { 1.0 + 3.0 * forward.powi(4) };
+    // Ley de Lommel-Seeliger, la habitual para capas de partículas como los anillos
+    let brightness = 2.0 * sun_slant / (sun_slant + view_slant);
+
+    // La sombra del planeta también apaga la luz ambiental del anillo
+    let sunlight = uniforms.lighting.sun_radiance() * (brightness * transmitted * phase * shadow);
+    let light = uniforms.lighting.ambient() * (0.3 + 0.7 * shadow) + sunlight;
+    let lit = color.component_mul(&light);
+    Color::from_float(lit.x, lit.y, lit.z)
+}
 
-    let lit_color = base_color * smooth_edge * final_light_factor;
+// Opacidad del anillo según su profundidad óptica y lo inclinada que se mire
+pub fn ring_alpha_shader(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    let Some(profile) = &uniforms.rings else {
+        return 0.0;
+    };
+    let normal = normalize(&fragment.world_normal);
+    let cos_angle = dot(&normal, &fragment.view_dir);
+    profile.opacity(ring_radius(fragment, uniforms), cos_angle, Some(&uniforms.noise))
+}
 
-    let noise = uniforms.noise.get_noise_2d(ring_position.x * 10.0, ring_position.y * 10.0) * 0.1;
-    lit_color * (1.0 + noise)
+// Radio en radios del planeta, a partir de la u de la malla de anillos
+fn ring_radius(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    let (inner, outer) = uniforms.ring_radii;
+    inner + (outer - inner) * fragment.uv.map_or(0.0, |uv| uv.x)
 }
 
 
@@ -499,8 +915,8 @@ pub fn mercury_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color
     let mid_color = Color::from_float(0.6, 0.55, 0.4);     // Gris dorado medio
     let dark_color = Color::from_float(0.3, 0.2, 0.1);     // Gris oscuro con toques cálidos
 
-    // Obtenemos la posición del fragmento
-    let position = Vec3::new(fragment.vertex_position.x, fragment.vertex_position.y, fragment.depth);
+    // Obtenemos la posición del fragmento sobre la superficie (coordenadas del modelo)
+    let position = fragment.vertex_position;
 
     // Factor de zoom para mayor detalle en la textura
     let zoom = 1200.0;
@@ -510,11 +926,10 @@ pub fn mercury_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color
     let noise_value2 = uniforms.noise.get_noise_3d((position.x + 400.0) * zoom, (position.y + 400.0) * zoom, (position.z + 400.0) * zoom);
     let noise_value = (noise_value1 + noise_value2) * 0.5;
 
-    // Parámetros para los cráteres en la superficie (más detalles)
-    let crater_frequency = 3.5;  // Aumentar la frecuencia de cráteres para más detalle
-    let crater_amplitude = 0.5;  // Aumentar la amplitud de los cráteres para que sean más visibles
-    let crater_value = ((position.x * crater_frequency).sin() * (position.y * crater_frequency).cos() * crater_amplitude)
-        .clamp(0.0, 1.0);
+    // Los cráteres del relieve oscurecen su fondo y aclaran el borde; los rayos de los
+    // cráteres jóvenes son claros
+    let (surface, craters) = terrain_fragment(fragment, uniforms);
+    let crater_value = craters.relief * 0.5 + craters.albedo * 0.6;
 
     // Combinamos el ruido base y el ruido de los cráteres
     let mut combined_value = (noise_value + crater_value).clamp(0.0, 1.0);
@@ -534,15 +949,8 @@ pub fn mercury_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color
         dark_color.lerp(&mid_color, combined_value * 2.0) // Color oscuro si el valor es bajo
     };
 
-    // Iluminación dinámica (ajustar la dirección para que la parte oscura esté en la parte trasera)
-    let light_position = Vec3::new(0.0, 0.0, 5.0);  // Luz principal (detrás del planeta)
-    let light_direction = (light_position - fragment.vertex_position).normalize(); // Dirección de la luz hacia el planeta
-    let normal = fragment.normal.normalize();  // Normal del fragmento
-    let diffuse_intensity = normal.dot(&light_direction).max(0.0); // Intensidad de la luz difusa
-
-    // Intensidad ambiental (ajustar para simular más reflexión en las superficies rocosas)
-    let ambient_intensity = 0.3;  
-    let lit_color = base_color * (ambient_intensity + (1.0 - ambient_intensity) * diffuse_intensity);
+    // Iluminación de la escena sobre el relieve
+    let lit_color = base_color * surface_light(&surface, uniforms);
 
     // Aplicar textura de sombra suave con ruido
     let shadow_texture_noise = uniforms.noise.get_noise_3d(
@@ -558,10 +966,7 @@ pub fn mercury_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color
     let pulsate = (uniforms.time as f32 * pulsate_frequency + position.x * 0.02 + position.y * 0.02).sin() * pulsate_amplitude;
     
     // Final color modificado por la pulsación
-    let final_color = final_color * (1.0 + pulsate);
-
-    // Devolvemos el color final multiplicado por la intensidad del fragmento
-    final_color * fragment.intensity
+    final_color * (1.0 + pulsate)
 }
 pub fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // Colores base para la luna (grises y tonos tierra)
@@ -571,37 +976,25 @@ pub fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
 
     // Coordenadas del fragmento
     let position = fragment.vertex_position;
-    let noise_scale = 50.0; // Escala para el ruido de cráteres
+    let noise_scale = 50.0; // Escala para el ruido de la superficie
 
-    // Generar ruido para simular cráteres y variaciones de textura
+    // Generar ruido para las variaciones de textura (zonas claras y oscuras)
     let noise_value = uniforms.noise.get_noise_2d(
         position.x * noise_scale,
         position.y * noise_scale,
     );
 
-    let fine_noise = uniforms.noise.get_noise_2d(
-        position.x * noise_scale * 2.0,
-        position.y * noise_scale * 2.0,
-    );
-
     // Mezclar colores en base al ruido
     let base_color = base_gray.lerp(&dark_gray, noise_value.clamp(0.0, 1.0));
 
-    // Añadir un efecto de borde brillante en los cráteres
-    let crater_effect = fine_noise.abs().clamp(0.0, 1.0);
-    let crater_color = base_color.lerp(&crater_highlight, crater_effect * 0.5);
-
-    // Iluminación difusa
-    let light_position = Vec3::new(2.0, 2.0, 5.0); // Posición de la luz (el "sol")
-    let light_dir = normalize(&(light_position - fragment.vertex_position));
-    let normal = normalize(&fragment.normal);
-    let diffuse = dot(&normal, &light_dir).max(0.0);
-
-    // Combinar iluminación con color base
-    let final_color = crater_color * (0.3 + 0.7 * diffuse);
+    // Fondos de cráter más oscuros, bordes y rayos de los cráteres jóvenes más claros
+    let (surface, craters) = terrain_fragment(fragment, uniforms);
+    let crater_color = base_color
+        .lerp(&dark_gray, (-craters.relief).clamp(0.0, 1.0) * 0.3)
+        .lerp(&crater_highlight, (craters.relief * 0.5 + craters.albedo).clamp(0.0, 1.0));
 
-    // Retornar color final
-    final_color * fragment.intensity
+    // Combinar la luz del Sol (con el eclipse lunar) con el color base sobre el relieve
+    crater_color * surface_light(&surface, uniforms)
 }
 pub fn moon_shader_wrapper(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     moon_shader(fragment, uniforms)
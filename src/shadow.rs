@@ -0,0 +1,120 @@
+use nalgebra_glm::{Vec2, Vec3, Vec4, Mat4, look_at, ortho};
+use crate::vertex::Vertex;
+use crate::triangle::triangle;
+
+// Mapa de profundidad visto desde el Sol. Se rasteriza con el mismo `triangle()` que la
+// escena y los shaders lo consultan para saber si un punto está a la sombra
+pub struct ShadowMap {
+    size: usize,
+    depth: Vec<f32>,
+    light_matrix: Mat4,
+    viewport_matrix: Mat4,
+    light_direction: Vec3,
+    depth_per_unit: f32,
+    texel_size: f32,
+}
+
+impl ShadowMap {
+    // `target` y `radius` delimitan la esfera de la escena que debe caber en el mapa
+    pub fn new(size: usize, light_position: Vec3, target: Vec3, radius: f32) -> Self {
+        let to_target = target - light_position;
+        let distance = to_target.magnitude();
+        let light_direction = -to_target / distance;
+
+        let up = if light_direction.y.abs() > 0.99 { Vec3::new(0.0, 0.0, 1.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+        let view_matrix = look_at(&light_position, &target, &up);
+        let near = (distance - radius).max(0.01);
+        let far = distance + radius;
+        let projection_matrix = ortho(-radius, radius, -radius, radius, near, far);
+
+        let half = size as f32 / 2.0;
+        let viewport_matrix = Mat4::new(
+            half, 0.0, 0.0, half,
+            0.0, -half, 0.0, half,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0
+        );
+
+        ShadowMap {
+            size,
+            depth: vec![f32::INFINITY; size * size],
+            light_matrix: projection_matrix * view_matrix,
+            viewport_matrix,
+            light_direction,
+            // La proyección ortográfica lleva [near, far] a [-1, 1]
+            depth_per_unit: 2.0 / (far - near),
+            texel_size: 2.0 * radius / size as f32,
+        }
+    }
+
+    // Dirección desde la escena hacia la luz
+    pub fn light_direction(&self) -> Vec3 {
+        self.light_direction
+    }
+
+    fn project(&self, world_position: &Vec3) -> Vec3 {
+        let clip = self.light_matrix * Vec4::new(world_position.x, world_position.y, world_position.z, 1.0);
+        let screen = self.viewport_matrix * Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+        Vec3::new(screen.x, screen.y, screen.z)
+    }
+
+    // Pasada de solo profundidad para un cuerpo que proyecta sombra
+    pub fn render_caster(&mut self, vertex_array: &[Vertex], model_matrix: &Mat4) {
+        let transformed: Vec<Vertex> = vertex_array
+            .iter()
+            .map(|vertex| {
+                let world = model_matrix * Vec4::new(vertex.position.x, vertex.position.y, vertex.position.z, 1.0);
+                Vertex {
+                    transformed_position: self.project(&world.xyz()),
+                    ..vertex.clone()
+                }
+            })
+            .collect();
+
+        let center = [Vec2::new(0.5, 0.5)];
+        for tri in transformed.chunks_exact(3) {
+            for fragment in triangle(&tri[0], &tri[1], &tri[2], &center) {
+                let x = fragment.position.x as usize;
+                let y = fragment.position.y as usize;
+                if x < self.size && y < self.size {
+                    let index = y * self.size + x;
+                    self.depth[index] = self.depth[index].min(fragment.depth);
+                }
+            }
+        }
+    }
+
+    // Fracción de luz que llega al punto (0 = sombra total), filtrada con PCF en una ventana de 5x5
+    pub fn visibility(&self, world_position: &Vec3, normal: &Vec3) -> f32 {
+        // Las caras que no miran al Sol ya quedan oscuras por el término difuso; consultarlas
+        // solo agregaría auto-sombra ruidosa en el terminador
+        let facing = normal.dot(&self.light_direction);
+        if facing <= 0.0 {
+            return 1.0;
+        }
+        // Para evitar el "shadow acne" el punto se desplaza un poco sobre la normal y el sesgo
+        // de profundidad crece con la inclinación respecto a la luz (tangente del ángulo)
+        let offset_position = world_position + normal * (self.texel_size * 1.5);
+        let projected = self.project(&offset_position);
+        let slope = (1.0 - facing * facing).max(0.0).sqrt() / facing.max(1e-3);
+        let bias = self.texel_size * (1.0 + 2.5 * slope.min(10.0)) * self.depth_per_unit;
+
+        let (cx, cy) = (projected.x.floor() as i32, projected.y.floor() as i32);
+        let mut lit = 0.0;
+        let mut total = 0.0;
+        for dy in -2..=2 {
+            for dx in -2..=2 {
+                let (x, y) = (cx + dx, cy + dy);
+                total += 1.0;
+                if x < 0 || y < 0 || x >= self.size as i32 || y >= self.size as i32 {
+                    lit += 1.0;
+                    continue;
+                }
+                if projected.z - bias <= self.depth[y as usize * self.size + x as usize] {
+                    lit += 1.0;
+                }
+            }
+        }
+        lit / total
+    }
+}
@@ -0,0 +1,134 @@
+use nalgebra_glm::{Vec3, cross, dot, normalize};
+use fastnoise_lite::{FastNoiseLite, NoiseType, FractalType};
+use std::sync::OnceLock;
+use crate::craters::{CraterDistribution, CraterField, CraterSample};
+
+// Ruido que comparten todos los relieves: crestas para las montañas
+pub struct TerrainNoise {
+    mountains: FastNoiseLite,
+}
+
+impl TerrainNoise {
+    pub fn new() -> Self {
+        let mut mountains = FastNoiseLite::with_seed(2024);
+        mountains.set_noise_type(Some(NoiseType::OpenSimplex2));
+        mountains.set_fractal_type(Some(FractalType::Ridged));
+        mountains.set_fractal_octaves(Some(4));
+        mountains.set_fractal_lacunarity(Some(2.0));
+        mountains.set_fractal_gain(Some(0.5));
+        mountains.set_frequency(Some(1.0));
+
+        TerrainNoise { mountains }
+    }
+}
+
+impl Default for TerrainNoise {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Relieve de un cuerpo rocoso. Las alturas son fracciones del radio de la malla
+#[derive(Debug, Clone)]
+pub struct Terrain {
+    pub mountain_height: f32,
+    pub mountain_scale: f32,
+    // Campo de cráteres generado al crear el relieve a partir de su distribución
+    pub craters: CraterField,
+}
+
+impl Terrain {
+    pub fn new(mountain_height: f32, mountain_scale: f32, craters: &CraterDistribution) -> Self {
+        Terrain {
+            mountain_height,
+            mountain_scale,
+            craters: CraterField::generate(craters),
+        }
+    }
+
+    // Los relieves de cada cuerpo se generan la primera vez que se piden y se comparten
+    // entre frames, así los cráteres no se vuelven a repartir en cada uno
+
+    // Marte: volcanes y cañones grandes, cráteres poco profundos por la erosión y sin rayos
+    pub fn mars() -> &'static Terrain {
+        static MARS: OnceLock<Terrain> = OnceLock::new();
+        MARS.get_or_init(|| {
+            Terrain::new(0.025, 1.5, &CraterDistribution {
+                seed: 11,
+                count: 700,
+                min_radius: 0.015,
+                max_radius: 0.25,
+                size_exponent: 1.8,
+                depth_ratio: 0.15,
+                ray_fraction: 0.0,
+            })
+        })
+    }
+
+    // Mercurio: casi sin montañas y lleno de cráteres profundos, algunos con rayos claros
+    pub fn mercury() -> &'static Terrain {
+        static MERCURY: OnceLock<Terrain> = OnceLock::new();
+        MERCURY.get_or_init(|| {
+            Terrain::new(0.01, 2.5, &CraterDistribution {
+                seed: 23,
+                count: 900,
+                min_radius: 0.012,
+                max_radius: 0.25,
+                size_exponent: 2.0,
+                depth_ratio: 0.2,
+                ray_fraction: 0.06,
+            })
+        })
+    }
+
+    pub fn moon() -> &'static Terrain {
+        static MOON: OnceLock<Terrain> = OnceLock::new();
+        MOON.get_or_init(|| {
+            Terrain::new(0.01, 2.0, &CraterDistribution {
+                seed: 47,
+                count: 900,
+                min_radius: 0.012,
+                max_radius: 0.3,
+                size_exponent: 1.8,
+                depth_ratio: 0.2,
+                ray_fraction: 0.05,
+            })
+        })
+    }
+
+    // Altura de la superficie en la dirección `direction` (desde el centro del cuerpo) y
+    // los cráteres en ese punto
+    fn sample(&self, noise: &TerrainNoise, direction: &Vec3) -> (f32, CraterSample) {
+        let direction = normalize(direction);
+
+        let mountain_point = direction * self.mountain_scale;
+        let ridges = noise.mountains.get_noise_3d(mountain_point.x, mountain_point.y, mountain_point.z);
+
+        let craters = self.craters.sample(&direction);
+        (ridges * self.mountain_height + craters.height, craters)
+    }
+
+    // Posición desplazada y normal recalculada de un punto de la esfera, junto con los
+    // cráteres del punto para que los shaders no los vuelvan a buscar. La normal sale de
+    // diferencias finitas a lo largo de dos tangentes de la superficie
+    pub fn displace(&self, noise: &TerrainNoise, position: &Vec3) -> (Vec3, Vec3, CraterSample) {
+        let radius = position.magnitude();
+        let direction = position / radius;
+        let surface = |direction: Vec3| normalize(&direction) * radius * (1.0 + self.sample(noise, &direction).0);
+
+        let reference = if direction.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let tangent = normalize(&cross(&direction, &reference));
+        let bitangent = cross(&direction, &tangent);
+
+        let epsilon = 0.003;
+        let (height, craters) = self.sample(noise, &direction);
+        let center = direction * radius * (1.0 + height);
+        let along_tangent = surface(direction + tangent * epsilon);
+        let along_bitangent = surface(direction + bitangent * epsilon);
+        let mut normal = normalize(&cross(&(along_tangent - center), &(along_bitangent - center)));
+        if dot(&normal, &direction) < 0.0 {
+            normal = -normal;
+        }
+        (center, normal, craters)
+    }
+}
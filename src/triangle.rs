@@ -1,38 +1,41 @@
-use nalgebra_glm::{Vec3, dot, Vec2};
+use nalgebra_glm::{Vec3, Vec2};
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
 use crate::color::Color;
 
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
+// `samples` are the sub-pixel sample positions of the framebuffer; with a single
+// sample at the center this is plain point sampling, with more it produces MSAA coverage
+pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, samples: &[Vec2]) -> Vec<Fragment> {
   let mut fragments = Vec::new();
   let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
 
   let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
 
-  let light_dir = Vec3::new(0.0, 0.0, 1.0);
-
   let triangle_area = edge_function(&a, &b, &c);
+  let depth_slope = calculate_depth_slope(&a, &b, &c, triangle_area);
 
   // Iterate over each pixel in the bounding box
   for y in min_y..=max_y {
     for x in min_x..=max_x {
-      let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
-
-      // Calculate barycentric coordinates
-      let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
+      // Which samples of this pixel fall inside the triangle
+      let mut coverage = 0u32;
+      for (s, offset) in samples.iter().enumerate() {
+        let sample = Vec3::new(x as f32 + offset.x, y as f32 + offset.y, 0.0);
+        let (w1, w2, w3) = barycentric_coordinates(&sample, &a, &b, &c, triangle_area);
+        if is_inside(w1, w2, w3) {
+          coverage |= 1 << s;
+        }
+      }
 
-      // Check if the point is inside the triangle
-      if w1 >= 0.0 && w1 <= 1.0 && 
-         w2 >= 0.0 && w2 <= 1.0 &&
-         w3 >= 0.0 && w3 <= 1.0 {
+      if coverage != 0 {
+        // Attributes are interpolated once per pixel, at its center
+        let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+        let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
 
         // Interpolate normal
         let normal = v1.transformed_normal * w1 + v2.transformed_normal * w2 + v3.transformed_normal * w3;
         let normal = normal.normalize();
 
-        // Calculate lighting intensity
-        let intensity = dot(&normal, &light_dir).max(0.0);
-
         // Create a gray color (unchanged)
         let color = Color::new(100, 100, 100); // Medium gray
 
@@ -41,16 +44,21 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
 
         // Positions of the original vertex
         let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
+        let uv = v1.tex_coords * w1 + v2.tex_coords * w2 + v3.tex_coords * w3;
 
-        fragments.push(Fragment::new(
+        let mut fragment = Fragment::new(
             Vec2::new(x as f32, y as f32),
             color,
             depth,
             normal,
-            intensity,
             vertex_position,
-            None
-        ));
+            Some(uv)
+        );
+        fragment.world_position = v1.world_position * w1 + v2.world_position * w2 + v3.world_position * w3;
+        fragment.view_dir = (v1.view_direction * w1 + v2.view_direction * w2 + v3.view_direction * w3).normalize();
+        fragment.coverage = coverage;
+        fragment.depth_slope = depth_slope;
+        fragments.push(fragment);
       }
     }
   }
@@ -67,6 +75,19 @@ fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i3
     (min_x, min_y, max_x, max_y)
 }
 
+fn is_inside(w1: f32, w2: f32, w3: f32) -> bool {
+    (0.0..=1.0).contains(&w1) &&
+    (0.0..=1.0).contains(&w2) &&
+    (0.0..=1.0).contains(&w3)
+}
+
+// Screen-space depth gradient (dz/dx, dz/dy), used to get the depth of each MSAA sample
+fn calculate_depth_slope(a: &Vec3, b: &Vec3, c: &Vec3, area: f32) -> Vec2 {
+    let dz_dx = (a.z * (c.y - b.y) + b.z * (a.y - c.y) + c.z * (b.y - a.y)) / area;
+    let dz_dy = -(a.z * (c.x - b.x) + b.z * (a.x - c.x) + c.z * (b.x - a.x)) / area;
+    Vec2::new(dz_dx, dz_dy)
+}
+
 fn barycentric_coordinates(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3, area: f32) -> (f32, f32, f32) {
     let w1 = edge_function(b, c, p) / area;
     let w2 = edge_function(c, a, p) / area;
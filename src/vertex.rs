@@ -6,9 +6,14 @@ pub struct Vertex {
   pub position: Vec3,
   pub normal: Vec3,
   pub tex_coords: Vec2,
+  // Dirección en la que crece la coordenada u sobre la superficie (cero si la malla no la trae)
+  pub tangent: Vec3,
   pub color: Color,
   pub transformed_position: Vec3,
   pub transformed_normal: Vec3,
+  // Posición en el mundo y vector desde la cámara hasta el vértice (sin normalizar, así se interpola bien)
+  pub world_position: Vec3,
+  pub view_direction: Vec3,
 }
 
 impl Vertex {
@@ -17,23 +22,31 @@ impl Vertex {
       position,
       normal,
       tex_coords,
+      tangent: Vec3::new(0.0, 0.0, 0.0),
       color: Color::black(),
       transformed_position: position,
       transformed_normal: normal,
+      world_position: position,
+      view_direction: Vec3::new(0.0, 0.0, -1.0),
     }
   }
 
+  #[allow(dead_code)]
   pub fn new_with_color(position: Vec3, color: Color) -> Self {
     Vertex {
       position,
       normal: Vec3::new(0.0, 0.0, 0.0),
       tex_coords: Vec2::new(0.0, 0.0),
+      tangent: Vec3::new(0.0, 0.0, 0.0),
       color,
       transformed_position: Vec3::new(0.0, 0.0, 0.0),
       transformed_normal: Vec3::new(0.0, 0.0, 0.0),
+      world_position: position,
+      view_direction: Vec3::new(0.0, 0.0, -1.0),
     }
   }
 
+  #[allow(dead_code)]
   pub fn set_transformed(&mut self, position: Vec3, normal: Vec3) {
     self.transformed_position = position;
     self.transformed_normal = normal;
@@ -46,9 +59,12 @@ impl Default for Vertex {
       position: Vec3::new(0.0, 0.0, 0.0),
       normal: Vec3::new(0.0, 1.0, 0.0),
       tex_coords: Vec2::new(0.0, 0.0),
+      tangent: Vec3::new(1.0, 0.0, 0.0),
       color: Color::black(),
       transformed_position: Vec3::new(0.0, 0.0, 0.0),
       transformed_normal: Vec3::new(0.0, 1.0, 0.0),
+      world_position: Vec3::new(0.0, 0.0, 0.0),
+      view_direction: Vec3::new(0.0, 0.0, -1.0),
     }
   }
 }
\ No newline at end of file